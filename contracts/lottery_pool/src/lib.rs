@@ -1,27 +1,103 @@
 #![no_std]
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
-    contract, contractimpl, contracttype, symbol_short, vec, Address, Env, IntoVal, Symbol, Vec,
+    contract, contractimpl, contracttype, symbol_short, vec, Address, Env, IntoVal, Map, String,
+    Symbol, Vec,
 };
 
 // Constants
 const INSTANCE_LIFETIME_THRESHOLD: u32 = 518400; // ~60 days
 const INSTANCE_BUMP_AMOUNT: u32 = 1036800; // ~120 days
+const PERSISTENT_LIFETIME_THRESHOLD: u32 = 518400; // ~60 days
+const PERSISTENT_BUMP_AMOUNT: u32 = 1036800; // ~120 days
+const MAX_BATCH_ENTRIES: u32 = 15; // enter_batch beneficiaries per call, two new entries each
+const BLEND_SCALAR_12: i128 = 1_000_000_000_000; // Fixed-point scale of Blend's b_rate
 
 #[contracttype]
 pub enum DataKey {
     Admin,
-    UsdcToken,
-    BlendPool, // Blend pool address for yield generation
-    CurrentRound,
-    Round(u32),
-    PlayerDeposit(u32, Address),
-    PlayerList(u32),
-    YieldRate, // Kept for fallback/reference
-    RoundDuration,
-    MinDeposit,
-    TotalVolume,
-    TotalPlayers,
+    Assets, // One sub-pool per asset: any Blend reserve token (USDC, EURC, XLM via its SAC, ...)
+    SwapRouter, // Optional Soroswap-style router for deposits in other assets
+    EmissionToken, // BLND, earned by the Blend positions
+    // Everything below belongs to the sub-pool of the asset it is keyed by
+    Decimals(Address),   // Read from the asset when its sub-pool is added
+    BlendPool(Address),  // Blend pool address for yield generation
+    BlendPools(Address), // Pools deposits are spread across, defaults to BlendPool alone
+    CurrentRound(Address),
+    Round(Address, u32),
+    PlayerDeposit(Address, u32, Address),
+    PlayerAt(Address, u32, u32), // (round, index) -> player, indices 0..Round.player_count
+    Draw(Address, u32),          // Settlement progress of a drawing round
+    RoundSummary(Address, u32),  // Kept once a round's data has been pruned
+    YieldRate(Address),          // Basis points per draw paid by the mock strategy
+    YieldStrategy(Address),
+    PoolPrincipal(Address, Address), // Principal supplied to a Blend pool, excluding accrued interest
+    ExitHeld(Address),               // Principal an unfinished emergency exit holds in the contract
+    RoundDuration(Address),
+    MinDeposit(Address),
+    MaxDepositPerPlayer(Address), // Deposit caps, 0 = no limit
+    MaxRoundDeposits(Address),
+    MaxPlayersPerRound(Address),
+    CloseWhenFull(Address),   // End the round as soon as it reaches a cap
+    EarlyExitFee(Address),    // Basis points kept from early withdrawals and added to the prize
+    ClaimWindow(Address),     // Seconds players have to claim refunds and prizes, 0 = no deadline
+    UnclaimedPolicy(Address), // What happens to swept funds
+    PendingClaims(Address),   // Refunds and prizes owed to players, held by the contract
+    UnclaimedFunds(Address),  // Swept funds awaiting admin action
+    TotalVolume(Address),
+    TotalPlayers(Address),
+    TotalPrizesPaid(Address),
+    TotalEmissions(Address), // Harvested emissions, in pool-asset units after the swap
+    UnsoldEmissions(Address), // BLND claimed from dropped Blend pools, swapped at the next harvest
+    QueueHead(Address),      // Next queued withdrawal to pay
+    QueueTail(Address),      // Where the next queued withdrawal goes
+    Queued(Address, u32),    // Claim waiting for strategy liquidity
+    QueuedTotal(Address),    // Owed to queued claims
+    Parked(Address, Address), // Queued claims whose transfer failed, for the recipient to collect
+    Mode(Address),
+    Account(Address, Address), // Savings-mode standing balance
+    AccountAt(Address, u32),   // index -> savings account holder
+    AccountCount(Address),
+    SavingsTotals(Address),
+}
+
+/// Where deposits are put to work between entry and draw
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum YieldStrategy {
+    Blend,       // Supplied to the Blend pool as collateral
+    HouseFunded, // Held by the contract; prizes come from house top-ups
+    Mock,        // Held by the contract; pays YieldRate of deposits per draw from the house balance
+}
+
+/// How players take part in the pool
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoolMode {
+    Rounds,  // Per-round entries with refunds after every draw
+    Savings, // Standing balances drawn over every RoundDuration by time-weighted balance
+}
+
+/// What `sweep_unclaimed` does with expired refunds and prizes
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnclaimedPolicy {
+    Hold,    // Keep in the unclaimed bucket until the admin returns or rolls them
+    Jackpot, // Roll straight into the current round's jackpot
+}
+
+/// Lifecycle of a round. `AwaitingDraw` is never stored: an `Open` round
+/// reports it once `end_time` has passed (see `effective_status`).
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoundStatus {
+    Open,
+    AwaitingDraw,
+    Drawing,
+    Settled,
+    RolledOver,
+    Cancelled,
+    EmergencyExited,
 }
 
 #[contracttype]
@@ -31,9 +107,17 @@ pub struct Round {
     pub start_time: u64,
     pub end_time: u64,
     pub total_deposits: i128,
+    pub renewing_deposits: i128, // Part of total_deposits carried into the next round
     pub total_yield: i128,
+    pub jackpot: i128, // Carried over from rolled-over or cancelled rounds, held by the contract
     pub winner: Option<Address>,
-    pub is_active: bool,
+    pub prize: i128,         // Recorded at the draw, paid out by `claim_prize`
+    pub claim_deadline: u64, // 0 = refunds and prize can be claimed at any time
+    pub outstanding: i128,   // Refunds and prize not yet claimed or swept
+    pub principal_outstanding: i128, // Part of outstanding still supplied to the strategy
+    pub prize_claimed: bool,
+    pub seed: u64, // Draw randomness, 0 until drawn
+    pub status: RoundStatus,
     pub player_count: u32,
 }
 
@@ -44,6 +128,111 @@ pub struct PlayerEntry {
     pub deposit: i128,
     pub round_id: u32,
     pub has_claimed: bool,
+    pub index: u32,            // Position in the round's PlayerAt list
+    pub auto_renew: bool,      // Carry the deposit into the next round instead of refunding it
+    pub rounds_remaining: u32, // Further rounds the deposit is committed to
+}
+
+/// Progress of a draw that settles across several transactions
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DrawState {
+    pub rollover: bool,       // Too few players: no winner, yield goes to the jackpot
+    pub winning_ticket: i128, // Tickets are deposit units, numbered in player order
+    pub cursor: u32,          // Next player to walk
+    pub tickets_seen: i128,   // Tickets held by the players walked so far
+    pub winner: Option<Address>, // Set once the walk passes the winning ticket
+}
+
+/// Standing balance of a savings-mode player. Time-weighted balances are
+/// accumulated lazily and kept for the last finished round until it is drawn.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SavingsAccount {
+    pub player: Address,
+    pub balance: i128,
+    pub index: u32,    // Position in the AccountAt list
+    pub round_id: u32, // Round `twab` accumulates for
+    pub twab: i128,    // balance x seconds within `round_id` up to `last_update`
+    pub last_update: u64,
+    pub prev_round_id: u32, // Previous round, whose weight may still be needed by its draw
+    pub prev_twab: i128,
+}
+
+/// Pool-wide savings balance and its time-weighted total for the current round
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SavingsTotals {
+    pub total_balance: i128,
+    pub twab: i128,
+    pub last_update: u64,
+}
+
+/// A Blend pool and its target share of supplied principal
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolAllocation {
+    pub pool: Address,
+    pub weight: u32, // Relative to the sum of all weights
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolExposure {
+    pub pool: Address,
+    pub weight: u32,
+    pub principal: i128,
+    pub value: i128, // Principal plus accrued interest
+}
+
+/// Claim waiting for the yield strategy to have enough liquidity
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedWithdrawal {
+    pub recipient: Address,
+    pub amount: i128,    // Paid out when fulfilled
+    pub principal: i128, // Part of amount still supplied to the strategy
+    pub round_id: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalQueue {
+    pub head: u32,
+    pub tail: u32,
+    pub total: i128,
+}
+
+/// Compact record of a finished round that survives `prune_round`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundSummary {
+    pub id: u32,
+    pub status: RoundStatus,
+    pub winner: Option<Address>,
+    pub prize: i128,
+    pub total_deposits: i128,
+    pub total_yield: i128,
+    pub player_count: u32,
+    pub seed: u64,
+}
+
+/// Room left in the current round, `None` where no cap is set
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundCapacity {
+    pub round_id: u32,
+    pub remaining_deposits: Option<i128>,
+    pub remaining_players: Option<u32>,
+}
+
+/// Token the pool takes deposits in; amounts are in its base units
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetInfo {
+    pub token: Address,
+    pub decimals: u32,
+    pub min_deposit: i128,
 }
 
 #[contracttype]
@@ -53,6 +242,7 @@ pub struct GlobalStats {
     pub total_volume: i128,
     pub total_players: u32,
     pub total_prizes_paid: i128,
+    pub total_emissions: i128,
 }
 
 // ============ BLEND REQUEST STRUCTURE ============
@@ -66,234 +256,2685 @@ pub struct Request {
     pub amount: i128,      // Amount
 }
 
+// ============ BLEND POOL STATE ============
+// From blend-contracts-v2/pool/src/storage.rs and pool/src/pool/{reserve,user}.rs
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReserveConfig {
+    pub index: u32, // Reserve index, keys the maps in Positions
+    pub decimals: u32,
+    pub c_factor: u32,
+    pub l_factor: u32,
+    pub util: u32,
+    pub max_util: u32,
+    pub r_base: u32,
+    pub r_one: u32,
+    pub r_two: u32,
+    pub r_three: u32,
+    pub reactivity: u32,
+    pub supply_cap: i128,
+    pub enabled: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReserveData {
+    pub d_rate: i128,
+    pub b_rate: i128, // Underlying per b-token, 12 decimals
+    pub ir_mod: i128,
+    pub b_supply: i128,
+    pub d_supply: i128,
+    pub backstop_credit: i128,
+    pub last_time: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Reserve {
+    pub asset: Address,
+    pub config: ReserveConfig,
+    pub data: ReserveData,
+    pub scalar: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Positions {
+    pub liabilities: Map<u32, i128>, // reserve index -> d-tokens
+    pub collateral: Map<u32, i128>,  // reserve index -> b-tokens
+    pub supply: Map<u32, i128>,      // reserve index -> b-tokens
+}
+
 // ============ HELPER FUNCTIONS ============
 
 fn is_initialized(env: &Env) -> bool {
     env.storage().instance().has(&DataKey::Admin)
 }
 
-// ============ CONTRACT ============
+/// Status as seen at the current ledger time
+fn effective_status(env: &Env, round: &Round) -> RoundStatus {
+    if round.status == RoundStatus::Open && env.ledger().timestamp() >= round.end_time {
+        RoundStatus::AwaitingDraw
+    } else {
+        round.status
+    }
+}
+
+fn can_transition(from: RoundStatus, to: RoundStatus) -> bool {
+    use RoundStatus::*;
+    matches!(
+        (from, to),
+        (Open, Cancelled)
+            | (Open, EmergencyExited)
+            | (AwaitingDraw, Drawing)
+            | (AwaitingDraw, Cancelled)
+            | (AwaitingDraw, EmergencyExited)
+            | (Drawing, Settled)
+            | (Drawing, RolledOver)
+    )
+}
+
+/// Move a round to `to`, panicking on transitions the lifecycle doesn't allow
+fn transition(env: &Env, round: &mut Round, to: RoundStatus) {
+    let from = effective_status(env, round);
+    if !can_transition(from, to) {
+        panic!("Invalid round status transition");
+    }
+    round.status = to;
+}
+
+fn new_round(id: u32, start_time: u64, round_duration: u64, jackpot: i128) -> Round {
+    Round {
+        id,
+        start_time,
+        end_time: start_time + round_duration,
+        total_deposits: 0,
+        renewing_deposits: 0,
+        total_yield: 0,
+        jackpot,
+        winner: None,
+        prize: 0,
+        claim_deadline: 0,
+        outstanding: 0,
+        principal_outstanding: 0,
+        prize_claimed: false,
+        seed: 0,
+        status: RoundStatus::Open,
+        player_count: 0,
+    }
+}
+
+/// Open the round following `prev_round_id` and make it current
+fn start_next_round(env: &Env, asset: &Address, prev_round_id: u32, jackpot: i128) -> u32 {
+    let round_duration: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::RoundDuration(asset.clone()))
+        .unwrap();
+    let new_round_id = prev_round_id + 1;
+    let next_round = new_round(
+        new_round_id,
+        env.ledger().timestamp(),
+        round_duration,
+        jackpot,
+    );
+
+    env.storage()
+        .instance()
+        .set(&DataKey::CurrentRound(asset.clone()), &new_round_id);
+    save_round(env, asset, &next_round);
+
+    new_round_id
+}
+
+/// Extend the TTL of a persistent entry that is known to exist
+fn bump(env: &Env, key: &DataKey) {
+    env.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+fn save_round(env: &Env, asset: &Address, round: &Round) {
+    let key = DataKey::Round(asset.clone(), round.id);
+    env.storage().persistent().set(&key, round);
+    bump(env, &key);
+}
+
+fn get_i128(env: &Env, key: &DataKey) -> i128 {
+    env.storage().persistent().get(key).unwrap_or(0)
+}
+
+fn add_i128(env: &Env, key: &DataKey, delta: i128) {
+    let value = get_i128(env, key) + delta;
+    env.storage().persistent().set(key, &value);
+    bump(env, key);
+}
+
+/// Open the claim window of a round that just settled or was cancelled
+fn open_claims(env: &Env, asset: &Address, round: &mut Round, owed: i128, principal: i128) {
+    let claim_window: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::ClaimWindow(asset.clone()))
+        .unwrap_or(0);
+    if claim_window > 0 {
+        round.claim_deadline = env.ledger().timestamp() + claim_window;
+    }
+    round.outstanding = owed;
+    round.principal_outstanding = principal;
+    add_i128(env, &DataKey::PendingClaims(asset.clone()), owed);
+}
+
+/// Book a refund or prize payout against the round's outstanding claims
+fn record_claim(env: &Env, asset: &Address, round: &mut Round, amount: i128, principal: i128) {
+    if round.claim_deadline > 0 && env.ledger().timestamp() > round.claim_deadline {
+        panic!("Claim window has expired");
+    }
+    round.outstanding -= amount;
+    round.principal_outstanding -= principal;
+    save_round(env, asset, round);
+}
+
+/// Pay a refund or prize booked against `round`
+fn pay_claim(
+    env: &Env,
+    asset: &Address,
+    round: &mut Round,
+    to: &Address,
+    amount: i128,
+    principal: i128,
+) -> Option<u32> {
+    record_claim(env, asset, round, amount, principal);
+    pay_out(env, asset, to, amount, principal, round.id)
+}
+
+/// Send `amount` owed to `to`, bringing its `principal` part back from the
+/// yield strategy. When the strategy can't release it now, or earlier payouts
+/// are already waiting, it joins the withdrawal queue instead and its position
+/// there is returned. The amount counts as a pending claim until it is sent.
+fn pay_out(
+    env: &Env,
+    asset: &Address,
+    to: &Address,
+    amount: i128,
+    principal: i128,
+    round_id: u32,
+) -> Option<u32> {
+    if principal > 0 {
+        let queue = withdrawal_queue(env, asset);
+        if queue.head < queue.tail || strategy_liquidity(env, asset) < principal {
+            return Some(enqueue_withdrawal(
+                env, asset, to, amount, principal, round_id,
+            ));
+        }
+        strategy_withdraw(env, asset, principal);
+    }
+    add_i128(env, &DataKey::PendingClaims(asset.clone()), -amount);
+
+    env.invoke_contract::<()>(
+        asset,
+        &Symbol::new(env, "transfer"),
+        (env.current_contract_address(), to.clone(), amount).into_val(env),
+    );
+    None
+}
+
+fn withdrawal_queue(env: &Env, asset: &Address) -> WithdrawalQueue {
+    let storage = env.storage().persistent();
+    WithdrawalQueue {
+        head: storage.get(&DataKey::QueueHead(asset.clone())).unwrap_or(0),
+        tail: storage.get(&DataKey::QueueTail(asset.clone())).unwrap_or(0),
+        total: storage
+            .get(&DataKey::QueuedTotal(asset.clone()))
+            .unwrap_or(0),
+    }
+}
+
+/// Append a claim to the withdrawal queue, returning how many claims are ahead of it
+fn enqueue_withdrawal(
+    env: &Env,
+    asset: &Address,
+    recipient: &Address,
+    amount: i128,
+    principal: i128,
+    round_id: u32,
+) -> u32 {
+    let queue = withdrawal_queue(env, asset);
+    let entry = QueuedWithdrawal {
+        recipient: recipient.clone(),
+        amount,
+        principal,
+        round_id,
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Queued(asset.clone(), queue.tail), &entry);
+    bump(env, &DataKey::Queued(asset.clone(), queue.tail));
+    env.storage()
+        .persistent()
+        .set(&DataKey::QueueTail(asset.clone()), &(queue.tail + 1));
+    bump(env, &DataKey::QueueTail(asset.clone()));
+    add_i128(env, &DataKey::QueuedTotal(asset.clone()), amount);
+
+    env.events().publish(
+        (symbol_short!("queued"), asset.clone(), recipient.clone()),
+        (queue.tail, round_id, amount, asset_decimals(env, asset)),
+    );
+
+    queue.tail - queue.head
+}
+
+/// Decimals of the pool asset. Events carry them next to their amounts,
+/// which are always in the asset's base units.
+fn asset_decimals(env: &Env, asset: &Address) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Decimals(asset.clone()))
+        .unwrap()
+}
+
+/// Add to the current round's jackpot
+fn add_to_jackpot(env: &Env, asset: &Address, amount: i128) {
+    let current_round_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::CurrentRound(asset.clone()))
+        .unwrap_or_else(|| panic!("No pool for this asset"));
+    let mut round: Round = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Round(asset.clone(), current_round_id))
+        .unwrap();
+    round.jackpot += amount;
+    save_round(env, asset, &round);
+
+    env.events().publish(
+        (symbol_short!("jackpot"), asset.clone(), current_round_id),
+        (round.jackpot, asset_decimals(env, asset)),
+    );
+}
+
+fn summarize(round: &Round) -> RoundSummary {
+    RoundSummary {
+        id: round.id,
+        status: round.status,
+        winner: round.winner.clone(),
+        prize: round.prize,
+        total_deposits: round.total_deposits,
+        total_yield: round.total_yield,
+        player_count: round.player_count,
+        seed: round.seed,
+    }
+}
+
+/// Carry an auto-renewing deposit into the next round without moving tokens.
+/// Deposit caps don't apply: a committed deposit is never turned away.
+fn carry_entry(env: &Env, asset: &Address, next_round: &mut Round, entry: &PlayerEntry) {
+    let player_key = DataKey::PlayerDeposit(asset.clone(), next_round.id, entry.player.clone());
+    let existing: Option<PlayerEntry> = env.storage().persistent().get(&player_key);
+    let rounds_remaining = entry.rounds_remaining.saturating_sub(1);
+    let carried = match existing {
+        // The player already entered the next round: merge into that entry
+        Some(mut carried) => {
+            next_round.renewing_deposits -= renewing_amount(&carried);
+            carried.deposit += entry.deposit;
+            carried.auto_renew |= entry.auto_renew;
+            carried.rounds_remaining = carried.rounds_remaining.max(rounds_remaining);
+            carried
+        }
+        None => {
+            let index_key =
+                DataKey::PlayerAt(asset.clone(), next_round.id, next_round.player_count);
+            env.storage().persistent().set(&index_key, &entry.player);
+            bump(env, &index_key);
+
+            let carried = PlayerEntry {
+                player: entry.player.clone(),
+                deposit: entry.deposit,
+                round_id: next_round.id,
+                has_claimed: false,
+                index: next_round.player_count,
+                auto_renew: entry.auto_renew,
+                rounds_remaining,
+            };
+            next_round.player_count += 1;
+            carried
+        }
+    };
+    env.storage().persistent().set(&player_key, &carried);
+    bump(env, &player_key);
+
+    next_round.total_deposits += entry.deposit;
+    next_round.renewing_deposits += renewing_amount(&carried);
+
+    env.events().publish(
+        (
+            symbol_short!("renewed"),
+            asset.clone(),
+            entry.player.clone(),
+        ),
+        (next_round.id, entry.deposit, asset_decimals(env, asset)),
+    );
+}
+
+/// Current round, checked to be accepting entries
+fn open_round_for_entry(env: &Env, asset: &Address) -> Round {
+    let current_round_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::CurrentRound(asset.clone()))
+        .unwrap_or_else(|| panic!("No pool for this asset"));
+    let round: Round = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Round(asset.clone(), current_round_id))
+        .unwrap();
+
+    match effective_status(env, &round) {
+        RoundStatus::Open => {}
+        RoundStatus::AwaitingDraw => {
+            panic!("Round has ended, please wait for winner selection")
+        }
+        _ => panic!("Round is not active"),
+    }
+    round
+}
+
+/// Credit `amount` to `player`'s entry in `round`, creating it or topping it up.
+/// The caller moves the funds and saves the round.
+fn record_entry(
+    env: &Env,
+    asset: &Address,
+    round: &mut Round,
+    player: &Address,
+    amount: i128,
+    rounds: u32,
+) {
+    let min_deposit: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::MinDeposit(asset.clone()))
+        .unwrap();
+
+    if amount <= 0 {
+        panic!("Amount must be positive");
+    }
+
+    // A second entry tops up the existing deposit; only the first must meet the minimum
+    let player_key = DataKey::PlayerDeposit(asset.clone(), round.id, player.clone());
+    let existing: Option<PlayerEntry> = env.storage().persistent().get(&player_key);
+    if existing.is_none() && amount < min_deposit {
+        panic!("Deposit below minimum");
+    }
+    if rounds == 0 {
+        panic!("Must enter at least one round");
+    }
+
+    let max_per_player = get_cap(env, &DataKey::MaxDepositPerPlayer(asset.clone()));
+    let held = existing.as_ref().map(|entry| entry.deposit).unwrap_or(0);
+    if max_per_player > 0 && held + amount > max_per_player {
+        panic!("Deposit exceeds per-player cap");
+    }
+    let max_round_deposits = get_cap(env, &DataKey::MaxRoundDeposits(asset.clone()));
+    if max_round_deposits > 0 && round.total_deposits + amount > max_round_deposits {
+        panic!("Round deposit cap reached");
+    }
+    let max_players = max_players(env, asset);
+    if existing.is_none() && max_players > 0 && round.player_count >= max_players {
+        panic!("Round is full");
+    }
+
+    // A larger deposit holds more tickets in the draw
+    round.total_deposits += amount;
+    add_i128(env, &DataKey::TotalVolume(asset.clone()), amount);
+
+    if let Some(mut player_entry) = existing {
+        // A longer commitment on a top-up extends the whole deposit
+        round.renewing_deposits -= renewing_amount(&player_entry);
+        player_entry.deposit += amount;
+        player_entry.rounds_remaining = player_entry.rounds_remaining.max(rounds - 1);
+        round.renewing_deposits += renewing_amount(&player_entry);
+        env.storage().persistent().set(&player_key, &player_entry);
+        bump(env, &player_key);
+
+        env.events().publish(
+            (symbol_short!("topped_up"), asset.clone(), player.clone()),
+            (
+                round.id,
+                amount,
+                player_entry.deposit,
+                asset_decimals(env, asset),
+            ),
+        );
+        return;
+    }
+
+    // Store player entry
+    let player_entry = PlayerEntry {
+        player: player.clone(),
+        deposit: amount,
+        round_id: round.id,
+        has_claimed: false,
+        index: round.player_count,
+        auto_renew: false,
+        rounds_remaining: rounds - 1,
+    };
+    round.renewing_deposits += renewing_amount(&player_entry);
+
+    env.storage().persistent().set(&player_key, &player_entry);
+    bump(env, &player_key);
+
+    let index_key = DataKey::PlayerAt(asset.clone(), round.id, round.player_count);
+    env.storage().persistent().set(&index_key, player);
+    bump(env, &index_key);
+
+    round.player_count += 1;
+
+    // Update global stats
+    let total_players: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TotalPlayers(asset.clone()))
+        .unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&DataKey::TotalPlayers(asset.clone()), &(total_players + 1));
+    bump(env, &DataKey::TotalPlayers(asset.clone()));
+
+    // Emit event
+    env.events().publish(
+        (symbol_short!("entered"), asset.clone(), player.clone()),
+        (round.id, amount, rounds, asset_decimals(env, asset)),
+    );
+}
+
+fn get_cap(env: &Env, key: &DataKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn max_players(env: &Env, asset: &Address) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::MaxPlayersPerRound(asset.clone()))
+        .unwrap_or(0)
+}
+
+fn round_capacity(env: &Env, asset: &Address, round: &Round) -> RoundCapacity {
+    let max_round_deposits = get_cap(env, &DataKey::MaxRoundDeposits(asset.clone()));
+    let max_players = max_players(env, asset);
+    RoundCapacity {
+        round_id: round.id,
+        remaining_deposits: (max_round_deposits > 0)
+            .then(|| (max_round_deposits - round.total_deposits).max(0)),
+        remaining_players: (max_players > 0)
+            .then(|| max_players.saturating_sub(round.player_count)),
+    }
+}
+
+/// End the round's entry period early once it reaches a cap, if configured
+fn close_if_full(env: &Env, asset: &Address, round: &mut Round) {
+    let close_when_full: bool = env
+        .storage()
+        .instance()
+        .get(&DataKey::CloseWhenFull(asset.clone()))
+        .unwrap_or(false);
+    let now = env.ledger().timestamp();
+    if !close_when_full || round.end_time <= now {
+        return;
+    }
+
+    let capacity = round_capacity(env, asset, round);
+    if capacity.remaining_deposits == Some(0) || capacity.remaining_players == Some(0) {
+        round.end_time = now;
+        env.events()
+            .publish((symbol_short!("filled"), asset.clone(), round.id), ());
+    }
+}
+
+/// Move `amount` from `from` into the contract, either as a direct transfer
+/// authorized by `from` or under an allowance granted to the contract
+fn pull_deposit(env: &Env, asset: &Address, from: &Address, amount: i128, allowance: bool) {
+    let contract = env.current_contract_address();
+    if allowance {
+        env.invoke_contract::<()>(
+            asset,
+            &Symbol::new(env, "transfer_from"),
+            (contract.clone(), from.clone(), contract, amount).into_val(env),
+        );
+    } else {
+        env.invoke_contract::<()>(
+            asset,
+            &Symbol::new(env, "transfer"),
+            (from.clone(), contract, amount).into_val(env),
+        );
+    }
+}
+
+/// A sponsor may open a single-round entry for someone else on its own, but
+/// topping up an existing entry or committing future rounds changes what the
+/// beneficiary can withdraw, so it needs the beneficiary's authorization too
+fn require_sponsor_consent(
+    env: &Env,
+    asset: &Address,
+    round: &Round,
+    payer: &Address,
+    beneficiary: &Address,
+    rounds: u32,
+) {
+    if payer == beneficiary {
+        return;
+    }
+    let has_entry = env.storage().persistent().has(&DataKey::PlayerDeposit(
+        asset.clone(),
+        round.id,
+        beneficiary.clone(),
+    ));
+    if rounds > 1 || has_entry {
+        beneficiary.require_auth();
+    }
+}
+
+/// Shared entry path: `payer` funds a deposit credited to `beneficiary`
+fn enter(
+    env: &Env,
+    asset: &Address,
+    payer: &Address,
+    beneficiary: &Address,
+    amount: i128,
+    rounds: u32,
+    allowance: bool,
+) {
+    require_mode(env, asset, PoolMode::Rounds);
+
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+    let mut round = open_round_for_entry(env, asset);
+
+    // Step 1: Credit the ticket to the beneficiary
+    require_sponsor_consent(env, asset, &round, payer, beneficiary, rounds);
+    record_entry(env, asset, &mut round, beneficiary, amount, rounds);
+    close_if_full(env, asset, &mut round);
+    save_round(env, asset, &round);
+
+    // Step 2: Transfer the deposit from payer to lottery contract
+    pull_deposit(env, asset, payer, amount, allowance);
+
+    // Step 3: Supply to the yield strategy (Blend by default), only the new amount
+    strategy_supply(env, asset, amount);
+
+    if payer != beneficiary {
+        env.events().publish(
+            (symbol_short!("sponsored"), asset.clone(), payer.clone()),
+            (
+                beneficiary.clone(),
+                round.id,
+                amount,
+                asset_decimals(env, asset),
+            ),
+        );
+    }
+}
+
+/// Whether an entry's deposit carries into the next round
+fn renews(entry: &PlayerEntry) -> bool {
+    entry.auto_renew || entry.rounds_remaining > 0
+}
+
+/// Part of an entry's deposit counted in its round's `renewing_deposits`
+fn renewing_amount(entry: &PlayerEntry) -> i128 {
+    if renews(entry) {
+        entry.deposit
+    } else {
+        0
+    }
+}
+
+/// Walk up to `limit` players of a drawing round, counting tickets to find the
+/// owner of the winning ticket and carrying auto-renewing deposits into the
+/// next round. Once every player is walked, record the outcome and settle.
+fn settle(
+    env: &Env,
+    asset: &Address,
+    mut round: Round,
+    draw: &mut DrawState,
+    limit: u32,
+) -> Option<Address> {
+    let mut next_round: Round = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Round(asset.clone(), round.id + 1))
+        .unwrap();
+
+    let end = draw.cursor.saturating_add(limit).min(round.player_count);
+    while draw.cursor < end {
+        let index_key = DataKey::PlayerAt(asset.clone(), round.id, draw.cursor);
+        let player: Address = env.storage().persistent().get(&index_key).unwrap();
+        let player_key = DataKey::PlayerDeposit(asset.clone(), round.id, player);
+        let entry: PlayerEntry = env.storage().persistent().get(&player_key).unwrap();
+        // Entries must outlive the claim window
+        bump(env, &index_key);
+        bump(env, &player_key);
+
+        draw.cursor += 1;
+        if !draw.rollover && draw.winner.is_none() {
+            draw.tickets_seen += entry.deposit;
+            if draw.tickets_seen > draw.winning_ticket {
+                draw.winner = Some(entry.player.clone());
+            }
+        }
+        if renews(&entry) {
+            carry_entry(env, asset, &mut next_round, &entry);
+        }
+    }
+    close_if_full(env, asset, &mut next_round);
+    save_round(env, asset, &next_round);
+
+    if draw.cursor < round.player_count {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Draw(asset.clone(), round.id), draw);
+        bump(env, &DataKey::Draw(asset.clone(), round.id));
+        return None;
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Draw(asset.clone(), round.id));
+
+    // Deposits that don't renew are owed back to players; they stay supplied
+    // until claimed
+    let withdrawn = round.total_deposits - round.renewing_deposits;
+
+    let Some(winner) = draw.winner.clone() else {
+        // Every non-renewing player gets their deposit back via claim_refund
+        transition(env, &mut round, RoundStatus::RolledOver);
+        open_claims(env, asset, &mut round, withdrawn, withdrawn);
+        save_round(env, asset, &round);
+        return None;
+    };
+
+    // Record prize (yield + any carried jackpot, plus the original deposit
+    // unless it renewed into the next round); the winner collects it with claim_prize
+    let winner_entry: PlayerEntry = env
+        .storage()
+        .persistent()
+        .get(&DataKey::PlayerDeposit(
+            asset.clone(),
+            round.id,
+            winner.clone(),
+        ))
+        .unwrap();
+    let mut prize = round.total_yield + round.jackpot;
+    if !renews(&winner_entry) {
+        prize += winner_entry.deposit;
+    }
+
+    round.winner = Some(winner.clone());
+    round.prize = prize;
+    transition(env, &mut round, RoundStatus::Settled);
+    // Losers' refunds plus the prize
+    let owed = withdrawn + round.total_yield + round.jackpot;
+    open_claims(env, asset, &mut round, owed, withdrawn);
+
+    save_round(env, asset, &round);
+
+    env.events().publish(
+        (symbol_short!("winner"), asset.clone(), winner.clone()),
+        (round.id, prize, asset_decimals(env, asset)),
+    );
+
+    Some(winner)
+}
+
+// ============ SAVINGS HELPERS ============
+
+fn pool_mode(env: &Env, asset: &Address) -> PoolMode {
+    env.storage()
+        .instance()
+        .get(&DataKey::Mode(asset.clone()))
+        .unwrap_or(PoolMode::Rounds)
+}
+
+fn require_mode(env: &Env, asset: &Address, mode: PoolMode) {
+    if pool_mode(env, asset) != mode {
+        match mode {
+            PoolMode::Rounds => panic!("Pool is in savings mode"),
+            PoolMode::Savings => panic!("Pool is not in savings mode"),
+        }
+    }
+}
+
+fn savings_totals(env: &Env, asset: &Address) -> SavingsTotals {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SavingsTotals(asset.clone()))
+        .unwrap_or(SavingsTotals {
+            total_balance: 0,
+            twab: 0,
+            last_update: env.ledger().timestamp(),
+        })
+}
+
+fn save_savings_totals(env: &Env, asset: &Address, totals: &SavingsTotals) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SavingsTotals(asset.clone()), totals);
+    bump(env, &DataKey::SavingsTotals(asset.clone()));
+}
+
+/// Bring an account's time-weighted balance up to now. When the account last
+/// changed in an earlier round, the weight it earned in the round just drawn
+/// is kept in `prev_twab` for that round's settlement walk.
+fn accrue_account(env: &Env, asset: &Address, account: &mut SavingsAccount, current_round: &Round) {
+    let now = env.ledger().timestamp();
+    if account.round_id != current_round.id {
+        let prev_round_id = current_round.id - 1;
+        let prev_end = current_round.start_time;
+        account.prev_twab = if account.round_id == prev_round_id {
+            account.twab + account.balance * (prev_end - account.last_update) as i128
+        } else {
+            // Untouched for the whole previous round
+            let prev_start = env
+                .storage()
+                .persistent()
+                .get::<_, Round>(&DataKey::Round(asset.clone(), prev_round_id))
+                .map(|round| round.start_time)
+                .unwrap_or(prev_end);
+            account.balance * (prev_end - prev_start) as i128
+        };
+        account.prev_round_id = prev_round_id;
+        account.round_id = current_round.id;
+        account.twab = account.balance * (now - prev_end) as i128;
+    } else {
+        account.twab += account.balance * (now - account.last_update) as i128;
+    }
+    account.last_update = now;
+}
+
+fn accrue_totals(env: &Env, totals: &mut SavingsTotals) {
+    let now = env.ledger().timestamp();
+    totals.twab += totals.total_balance * (now - totals.last_update) as i128;
+    totals.last_update = now;
+}
+
+/// Start the time-weighted total afresh from now, for a round that opens
+/// without a draw of the previous one
+fn restart_savings_twab(env: &Env, asset: &Address) {
+    let mut totals = savings_totals(env, asset);
+    accrue_totals(env, &mut totals);
+    totals.twab = 0;
+    save_savings_totals(env, asset, &totals);
+}
+
+/// Time-weighted balance an account holds in `round`, which ended at `round_end`
+fn account_weight(account: &SavingsAccount, round: &Round, round_end: u64) -> i128 {
+    if account.round_id == round.id {
+        account.twab + account.balance * (round_end - account.last_update) as i128
+    } else if account.round_id < round.id {
+        account.balance * (round_end - round.start_time) as i128
+    } else if account.prev_round_id == round.id {
+        account.prev_twab
+    } else {
+        0
+    }
+}
+
+/// Savings-mode counterpart of `settle`: walk up to `limit` accounts,
+/// counting time-weighted tickets until the winning ticket's owner is found
+fn settle_savings(
+    env: &Env,
+    asset: &Address,
+    mut round: Round,
+    draw: &mut DrawState,
+    limit: u32,
+) -> Option<Address> {
+    let round_end = env
+        .storage()
+        .persistent()
+        .get::<_, Round>(&DataKey::Round(asset.clone(), round.id + 1))
+        .unwrap()
+        .start_time;
+
+    let end = draw.cursor.saturating_add(limit).min(round.player_count);
+    while draw.cursor < end && draw.winner.is_none() {
+        let index_key = DataKey::AccountAt(asset.clone(), draw.cursor);
+        let player: Address = env.storage().persistent().get(&index_key).unwrap();
+        let account_key = DataKey::Account(asset.clone(), player);
+        let account: SavingsAccount = env.storage().persistent().get(&account_key).unwrap();
+        // Accounts must outlive the draw like round entries do
+        bump(env, &index_key);
+        bump(env, &account_key);
+
+        draw.cursor += 1;
+        draw.tickets_seen += account_weight(&account, &round, round_end);
+        if draw.tickets_seen > draw.winning_ticket {
+            draw.winner = Some(account.player);
+        }
+    }
+
+    if draw.winner.is_none() && draw.cursor < round.player_count {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Draw(asset.clone(), round.id), draw);
+        bump(env, &DataKey::Draw(asset.clone(), round.id));
+        return None;
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Draw(asset.clone(), round.id));
+
+    let Some(winner) = draw.winner.clone() else {
+        // Every account was walked without reaching the winning ticket:
+        // the prize rolls into the round that is already open
+        transition(env, &mut round, RoundStatus::RolledOver);
+        save_round(env, asset, &round);
+        add_to_jackpot(env, asset, round.total_yield + round.jackpot);
+        return None;
+    };
+    // Nothing left to walk once the winner is known
+    draw.cursor = round.player_count;
+
+    let prize = round.total_yield + round.jackpot;
+    round.winner = Some(winner.clone());
+    round.prize = prize;
+    transition(env, &mut round, RoundStatus::Settled);
+    open_claims(env, asset, &mut round, prize, 0);
+    save_round(env, asset, &round);
+
+    env.events().publish(
+        (symbol_short!("winner"), asset.clone(), winner.clone()),
+        (round.id, prize, asset_decimals(env, asset)),
+    );
+
+    Some(winner)
+}
+
+/// Savings-mode draw: principal stays in Blend, the prize comes from the
+/// contract's idle balance and the odds follow time-weighted balances
+fn draw_savings(env: &Env, asset: &Address, mut round: Round, limit: u32) -> Option<Address> {
+    // Principal stays supplied; only the interest comes back for the prize
+    let interest = strategy_harvest(env, asset);
+    let balance: i128 = env.invoke_contract(
+        asset,
+        &Symbol::new(env, "balance"),
+        (env.current_contract_address(),).into_val(env),
+    );
+    // Close the round's time-weighted total; the next round starts now
+    let mut totals = savings_totals(env, asset);
+    accrue_totals(env, &mut totals);
+
+    let available_for_yield = balance
+        - idle_principal(env, asset, totals.total_balance)
+        - round.jackpot
+        - get_i128(env, &DataKey::PendingClaims(asset.clone()))
+        - get_i128(env, &DataKey::UnclaimedFunds(asset.clone()));
+    let total_yield = draw_yield(
+        env,
+        asset,
+        totals.total_balance,
+        available_for_yield,
+        interest,
+    );
+    let total_weight = totals.twab;
+    totals.twab = 0;
+    save_savings_totals(env, asset, &totals);
+
+    round.total_deposits = totals.total_balance;
+    round.total_yield = total_yield;
+    round.player_count = env
+        .storage()
+        .persistent()
+        .get(&DataKey::AccountCount(asset.clone()))
+        .unwrap_or(0);
+
+    if total_weight == 0 {
+        // Nobody held a balance: yield rolls to the next round as jackpot
+        transition(env, &mut round, RoundStatus::RolledOver);
+        save_round(env, asset, &round);
+
+        let jackpot = round.jackpot + total_yield;
+        let new_round_id = start_next_round(env, asset, round.id, jackpot);
+        env.events().publish(
+            (symbol_short!("jackpot"), asset.clone(), new_round_id),
+            (jackpot, asset_decimals(env, asset)),
+        );
+        return None;
+    }
+
+    round.seed = env.ledger().timestamp() ^ (env.ledger().sequence() as u64);
+    let mut draw = DrawState {
+        rollover: false,
+        winning_ticket: round.seed as i128 % total_weight,
+        cursor: 0,
+        tickets_seen: 0,
+        winner: None,
+    };
+    save_round(env, asset, &round);
+    start_next_round(env, asset, round.id, 0);
+
+    settle_savings(env, asset, round, &mut draw, limit)
+}
+
+/// Load (or open) a savings account and accrue it to now
+fn load_account(
+    env: &Env,
+    asset: &Address,
+    player: &Address,
+    current_round: &Round,
+) -> SavingsAccount {
+    let account: Option<SavingsAccount> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Account(asset.clone(), player.clone()));
+    match account {
+        Some(mut account) => {
+            accrue_account(env, asset, &mut account, current_round);
+            account
+        }
+        None => {
+            let index: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::AccountCount(asset.clone()))
+                .unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&DataKey::AccountAt(asset.clone(), index), player);
+            bump(env, &DataKey::AccountAt(asset.clone(), index));
+            env.storage()
+                .persistent()
+                .set(&DataKey::AccountCount(asset.clone()), &(index + 1));
+            bump(env, &DataKey::AccountCount(asset.clone()));
+
+            SavingsAccount {
+                player: player.clone(),
+                balance: 0,
+                index,
+                round_id: current_round.id,
+                twab: 0,
+                last_update: env.ledger().timestamp(),
+                prev_round_id: 0,
+                prev_twab: 0,
+            }
+        }
+    }
+}
+
+fn save_account(env: &Env, asset: &Address, account: &SavingsAccount) {
+    let key = DataKey::Account(asset.clone(), account.player.clone());
+    env.storage().persistent().set(&key, account);
+    bump(env, &key);
+    bump(env, &DataKey::AccountAt(asset.clone(), account.index));
+}
+
+// ============ BLEND HELPERS ============
+
+fn blend_submit(env: &Env, blend_pool: &Address, request: Request) {
+    let requests = Vec::from_array(env, [request]);
+
+    let _ = env.invoke_contract::<soroban_sdk::Val>(
+        blend_pool,
+        &Symbol::new(env, "submit"),
+        (
+            env.current_contract_address(),
+            env.current_contract_address(),
+            env.current_contract_address(),
+            requests,
+        )
+            .into_val(env),
+    );
+}
+
+/// Deposit to Blend pool as SupplyCollateral
+fn blend_supply(env: &Env, asset: &Address, blend_pool: &Address, amount: i128) {
+    // Authorize lottery contract for nested calls
+    // This authorizes Blend to call token.transfer on behalf of lottery contract
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: asset.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (env.current_contract_address(), blend_pool.clone(), amount).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    blend_submit(
+        env,
+        blend_pool,
+        Request {
+            request_type: 2, // SupplyCollateral = 2 (earns yield!)
+            address: asset.clone(),
+            amount,
+        },
+    );
+}
+
+/// Withdraw from Blend pool (WithdrawCollateral)
+fn blend_withdraw(env: &Env, asset: &Address, blend_pool: &Address, amount: i128) {
+    // Authorize withdrawal (Blend will transfer the asset back to lottery)
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: asset.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (blend_pool.clone(), env.current_contract_address(), amount).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    blend_submit(
+        env,
+        blend_pool,
+        Request {
+            request_type: 3, // WithdrawCollateral = 3
+            address: asset.clone(),
+            amount,
+        },
+    );
+}
+
+/// Current value, in the asset, of the contract's collateral in the Blend pool
+fn blend_position_value(env: &Env, asset: &Address, blend_pool: &Address) -> i128 {
+    // get_reserve accrues interest up to the current ledger
+    let reserve: Reserve = env.invoke_contract(
+        blend_pool,
+        &Symbol::new(env, "get_reserve"),
+        (asset.clone(),).into_val(env),
+    );
+    let positions: Positions = env.invoke_contract(
+        blend_pool,
+        &Symbol::new(env, "get_positions"),
+        (env.current_contract_address(),).into_val(env),
+    );
+
+    let b_tokens = positions.collateral.get(reserve.config.index).unwrap_or(0);
+    b_tokens * reserve.data.b_rate / BLEND_SCALAR_12
+}
+
+/// Amount of the asset the Blend reserve can pay out right now: supplied
+/// funds not lent out
+fn blend_liquidity(env: &Env, asset: &Address, blend_pool: &Address) -> i128 {
+    let reserve: Reserve = env.invoke_contract(
+        blend_pool,
+        &Symbol::new(env, "get_reserve"),
+        (asset.clone(),).into_val(env),
+    );
+    let supplied = reserve.data.b_supply * reserve.data.b_rate / BLEND_SCALAR_12;
+    let borrowed = reserve.data.d_supply * reserve.data.d_rate / BLEND_SCALAR_12;
+    (supplied - borrowed).max(0)
+}
+
+/// Claim the BLND emitted to the contract's collateral position.
+/// Emission ids are reserve index * 2 for d-tokens and * 2 + 1 for b-tokens.
+fn blend_claim_emissions(env: &Env, asset: &Address, blend_pool: &Address) -> i128 {
+    let reserve: Reserve = env.invoke_contract(
+        blend_pool,
+        &Symbol::new(env, "get_reserve"),
+        (asset.clone(),).into_val(env),
+    );
+    let reserve_token_ids = vec![env, reserve.config.index * 2 + 1];
+
+    env.invoke_contract(
+        blend_pool,
+        &Symbol::new(env, "claim"),
+        (
+            env.current_contract_address(),
+            reserve_token_ids,
+            env.current_contract_address(),
+        )
+            .into_val(env),
+    )
+}
+
+// ============ YIELD STRATEGY ============
+
+fn yield_strategy(env: &Env, asset: &Address) -> YieldStrategy {
+    env.storage()
+        .instance()
+        .get(&DataKey::YieldStrategy(asset.clone()))
+        .unwrap_or(YieldStrategy::Blend)
+}
+
+fn blend_pools(env: &Env, asset: &Address) -> Vec<PoolAllocation> {
+    env.storage()
+        .instance()
+        .get(&DataKey::BlendPools(asset.clone()))
+        .unwrap_or_else(|| {
+            let blend_pool: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::BlendPool(asset.clone()))
+                .unwrap();
+            vec![
+                env,
+                PoolAllocation {
+                    pool: blend_pool,
+                    weight: 1,
+                },
+            ]
+        })
+}
+
+fn pool_principal(env: &Env, asset: &Address, pool: &Address) -> i128 {
+    get_i128(env, &DataKey::PoolPrincipal(asset.clone(), pool.clone()))
+}
+
+/// Principal a pool should hold out of `total` under its weight
+fn pool_target(allocation: &PoolAllocation, total: i128, total_weight: u32) -> i128 {
+    total * allocation.weight as i128 / total_weight as i128
+}
+
+/// Principal that can be withdrawn from a pool right now
+fn pool_withdrawable(env: &Env, asset: &Address, pool: &Address) -> i128 {
+    pool_principal(env, asset, pool).min(blend_liquidity(env, asset, pool))
+}
+
+/// Put `amount` of newly deposited principal to work. In Blend it goes to the
+/// pool furthest below its target weight.
+fn strategy_supply(env: &Env, asset: &Address, amount: i128) {
+    if yield_strategy(env, asset) != YieldStrategy::Blend {
+        return;
+    }
+    // Nothing new goes to Blend while an emergency exit drains it
+    if let Some(held) = exit_held(env, asset) {
+        env.storage()
+            .instance()
+            .set(&DataKey::ExitHeld(asset.clone()), &(held + amount));
+        return;
+    }
+
+    let pools = blend_pools(env, asset);
+    let total_weight: u32 = pools.iter().map(|allocation| allocation.weight).sum();
+    let total: i128 = pools
+        .iter()
+        .map(|allocation| pool_principal(env, asset, &allocation.pool))
+        .sum::<i128>()
+        + amount;
+
+    let mut target_pool = pools.get(0).unwrap().pool;
+    let mut largest_deficit = i128::MIN;
+    for allocation in pools.iter() {
+        let deficit = pool_target(&allocation, total, total_weight)
+            - pool_principal(env, asset, &allocation.pool);
+        if deficit > largest_deficit {
+            largest_deficit = deficit;
+            target_pool = allocation.pool;
+        }
+    }
+
+    blend_supply(env, asset, &target_pool, amount);
+    add_i128(
+        env,
+        &DataKey::PoolPrincipal(asset.clone(), target_pool),
+        amount,
+    );
+}
+
+/// Bring `amount` of principal back into the contract; accrued interest
+/// stays supplied until the next draw collects it. Principal an emergency
+/// exit already holds is used first, then Blend pools in the order they are
+/// configured, each as far as its liquidity allows.
+fn strategy_withdraw(env: &Env, asset: &Address, amount: i128) {
+    if yield_strategy(env, asset) != YieldStrategy::Blend {
+        return;
+    }
+
+    let mut remaining = amount;
+    if let Some(held) = exit_held(env, asset) {
+        let take = remaining.min(held);
+        env.storage()
+            .instance()
+            .set(&DataKey::ExitHeld(asset.clone()), &(held - take));
+        remaining -= take;
+    }
+    for allocation in blend_pools(env, asset).iter() {
+        if remaining == 0 {
+            break;
+        }
+        let take = remaining.min(pool_withdrawable(env, asset, &allocation.pool));
+        if take > 0 {
+            blend_withdraw(env, asset, &allocation.pool, take);
+            add_i128(
+                env,
+                &DataKey::PoolPrincipal(asset.clone(), allocation.pool),
+                -take,
+            );
+            remaining -= take;
+        }
+    }
+    if remaining > 0 {
+        panic!("Not enough liquidity in Blend pools");
+    }
+}
+
+/// Withdraw the interest earned on supplied principal so far, leaving the
+/// principal in place. Blend positions are valued at the reserve's b_rate;
+/// other strategies earn no interest of their own. Only what each reserve can
+/// pay out now is harvested; the rest stays accrued for the next draw.
+fn strategy_harvest(env: &Env, asset: &Address) -> i128 {
+    if yield_strategy(env, asset) != YieldStrategy::Blend {
+        return 0;
+    }
+
+    let mut harvested = 0;
+    for allocation in blend_pools(env, asset).iter() {
+        let pool = allocation.pool;
+        let interest = (blend_position_value(env, asset, &pool)
+            - pool_principal(env, asset, &pool))
+        .min(blend_liquidity(env, asset, &pool))
+        .max(0);
+
+        if interest > 0 {
+            blend_withdraw(env, asset, &pool, interest);
+            harvested += interest;
+        }
+    }
+    harvested
+}
+
+/// How much principal the strategy can release right now
+fn strategy_liquidity(env: &Env, asset: &Address) -> i128 {
+    if yield_strategy(env, asset) != YieldStrategy::Blend {
+        return i128::MAX;
+    }
+    exit_held(env, asset).unwrap_or(0)
+        + blend_pools(env, asset)
+            .iter()
+            .map(|allocation| pool_withdrawable(env, asset, &allocation.pool))
+            .sum::<i128>()
+}
+
+/// Principal an emergency exit has brought back from Blend and not yet paid
+/// out, while it waits for the rest. None outside an exit.
+fn exit_held(env: &Env, asset: &Address) -> Option<i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::ExitHeld(asset.clone()))
+}
+
+/// Withdraw all the principal the Blend pools can release now into the
+/// contract. Once none is left in Blend the emergency exit is complete and
+/// the pool falls back to `HouseFunded`. Returns the amount withdrawn.
+fn drain_blend(env: &Env, asset: &Address) -> i128 {
+    let mut withdrawn = 0;
+    let mut remaining = 0;
+    for allocation in blend_pools(env, asset).iter() {
+        let take = pool_withdrawable(env, asset, &allocation.pool);
+        if take > 0 {
+            blend_withdraw(env, asset, &allocation.pool, take);
+            add_i128(
+                env,
+                &DataKey::PoolPrincipal(asset.clone(), allocation.pool.clone()),
+                -take,
+            );
+            withdrawn += take;
+        }
+        remaining += pool_principal(env, asset, &allocation.pool);
+    }
+
+    if remaining > 0 {
+        let held = exit_held(env, asset).unwrap_or(0) + withdrawn;
+        env.storage()
+            .instance()
+            .set(&DataKey::ExitHeld(asset.clone()), &held);
+    } else {
+        env.storage()
+            .instance()
+            .remove(&DataKey::ExitHeld(asset.clone()));
+        env.storage().instance().set(
+            &DataKey::YieldStrategy(asset.clone()),
+            &YieldStrategy::HouseFunded,
+        );
+        env.events().publish(
+            (symbol_short!("exited"), asset.clone()),
+            (withdrawn, asset_decimals(env, asset)),
+        );
+    }
+    withdrawn
+}
+
+/// Principal that stays in the contract's own balance between draws: only
+/// Blend takes deposits out of the contract
+fn idle_principal(env: &Env, asset: &Address, amount: i128) -> i128 {
+    match yield_strategy(env, asset) {
+        YieldStrategy::Blend => 0,
+        YieldStrategy::HouseFunded | YieldStrategy::Mock => amount,
+    }
+}
+
+/// Yield paid for a draw over `total_deposits`: the interest collected from
+/// Blend, or for the other strategies a share of the `available` funds that
+/// aren't owed to anyone
+fn draw_yield(
+    env: &Env,
+    asset: &Address,
+    total_deposits: i128,
+    available: i128,
+    interest: i128,
+) -> i128 {
+    match yield_strategy(env, asset) {
+        YieldStrategy::Blend => interest,
+        _ if available <= 0 => 0, // Safety: no yield if somehow we're short
+        // Use conservative yield: 5% of available (keeps house money for future rounds)
+        YieldStrategy::HouseFunded => available / 20,
+        YieldStrategy::Mock => {
+            let yield_rate: u32 = env
+                .storage()
+                .instance()
+                .get(&DataKey::YieldRate(asset.clone()))
+                .unwrap();
+            (total_deposits * yield_rate as i128 / 10000).min(available)
+        }
+    }
+}
+
+// ============ SWAP HELPERS ============
+
+/// Swap exactly `amount_in` of `token_in` held by the contract into the pool
+/// asset through a Soroswap-style router, returning the amount received
+fn swap_to_asset(
+    env: &Env,
+    router: &Address,
+    token_in: &Address,
+    asset: &Address,
+    amount_in: i128,
+    min_amount_out: i128,
+) -> i128 {
+    let path = vec![env, token_in.clone(), asset.clone()];
+
+    // The router moves the input from the contract straight into the pair
+    let pair: Address = env.invoke_contract(
+        router,
+        &Symbol::new(env, "router_pair_for"),
+        (token_in.clone(), asset.clone()).into_val(env),
+    );
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token_in.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (env.current_contract_address(), pair, amount_in).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    let amounts: Vec<i128> = env.invoke_contract(
+        router,
+        &Symbol::new(env, "swap_exact_tokens_for_tokens"),
+        (
+            amount_in,
+            min_amount_out,
+            path,
+            env.current_contract_address(),
+            env.ledger().timestamp(),
+        )
+            .into_val(env),
+    );
+
+    let amount_out = amounts.last().unwrap();
+    if amount_out < min_amount_out {
+        panic!("Swap returned less than minimum");
+    }
+    amount_out
+}
+
+/// Set up the sub-pool of `asset` and start its first round
+fn add_sub_pool(
+    env: &Env,
+    asset: &Address,
+    blend_pool: &Address,
+    yield_rate: u32,
+    round_duration: u64,
+    min_deposit: i128,
+) {
+    let mut assets = pool_assets(env);
+    if assets.contains(asset) {
+        panic!("Asset already has a pool");
+    }
+    if yield_rate > 10000 {
+        panic!("Yield rate cannot exceed 100%");
+    }
+    if round_duration < 60 {
+        panic!("Round duration must be at least 60 seconds");
+    }
+
+    let decimals: u32 = env.invoke_contract(asset, &Symbol::new(env, "decimals"), ().into_val(env));
+    // At least a hundredth of one whole unit of the asset
+    if min_deposit < 10i128.pow(decimals.saturating_sub(2)) {
+        panic!("Minimum deposit is below 0.01 of the asset");
+    }
+
+    assets.push_back(asset.clone());
+    env.storage().instance().set(&DataKey::Assets, &assets);
+    env.storage()
+        .instance()
+        .set(&DataKey::Decimals(asset.clone()), &decimals);
+    env.storage()
+        .instance()
+        .set(&DataKey::BlendPool(asset.clone()), blend_pool);
+    env.storage()
+        .instance()
+        .set(&DataKey::YieldRate(asset.clone()), &yield_rate);
+    env.storage()
+        .instance()
+        .set(&DataKey::RoundDuration(asset.clone()), &round_duration);
+    env.storage()
+        .instance()
+        .set(&DataKey::MinDeposit(asset.clone()), &min_deposit);
+
+    // Initialize the sub-pool's stats
+    add_i128(env, &DataKey::TotalVolume(asset.clone()), 0);
+    env.storage()
+        .persistent()
+        .set(&DataKey::TotalPlayers(asset.clone()), &0u32);
+    bump(env, &DataKey::TotalPlayers(asset.clone()));
+
+    let round = new_round(1, env.ledger().timestamp(), round_duration, 0);
+    env.storage()
+        .instance()
+        .set(&DataKey::CurrentRound(asset.clone()), &1u32);
+    save_round(env, asset, &round);
+
+    env.events().publish(
+        (symbol_short!("asset"), asset.clone()),
+        (decimals, min_deposit),
+    );
+}
+
+fn pool_assets(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Assets)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Panics unless `asset` has a sub-pool in this deployment
+fn require_asset(env: &Env, asset: &Address) {
+    if !env
+        .storage()
+        .instance()
+        .has(&DataKey::Decimals(asset.clone()))
+    {
+        panic!("No pool for this asset");
+    }
+}
+
+// ============ CONTRACT ============
+
+#[contract]
+pub struct LotteryPool;
+
+#[contractimpl]
+impl LotteryPool {
+    /// Initialize lottery pool with Blend integration, with a first sub-pool
+    /// for `asset_token`
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        asset_token: Address, // Any token listed as a reserve of the Blend pool
+        blend_pool: Address,  // Blend pool address
+        yield_rate: u32,
+        round_duration: u64,
+        min_deposit: i128,
+    ) {
+        if is_initialized(&env) {
+            panic!("Contract already initialized");
+        }
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        add_sub_pool(
+            &env,
+            &asset_token,
+            &blend_pool,
+            yield_rate,
+            round_duration,
+            min_deposit,
+        );
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Host another asset in this deployment. Its sub-pool has its own rounds,
+    /// savings balances, jackpot, withdrawal queue and Blend reserve, and every
+    /// pool entrypoint takes the asset it acts on.
+    pub fn add_asset(
+        env: Env,
+        asset_token: Address,
+        blend_pool: Address,
+        yield_rate: u32,
+        round_duration: u64,
+        min_deposit: i128,
+    ) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        add_sub_pool(
+            &env,
+            &asset_token,
+            &blend_pool,
+            yield_rate,
+            round_duration,
+            min_deposit,
+        );
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Enter lottery - deposits the pool asset and stakes in Blend for yield.
+    /// Entering again in the same round tops up the existing deposit.
+    /// `rounds` commits the deposit to this and the following rounds; it stays
+    /// in Blend and holds tickets in each of them before being refunded.
+    pub fn enter_lottery(env: Env, asset: Address, player: Address, amount: i128, rounds: u32) {
+        Self::enter_for(env, asset, player.clone(), player, amount, rounds);
+    }
+
+    /// Enter on someone else's behalf: `payer` funds the deposit while
+    /// `beneficiary` owns the ticket, the refund and any prize.
+    pub fn enter_for(
+        env: Env,
+        asset: Address,
+        payer: Address,
+        beneficiary: Address,
+        amount: i128,
+        rounds: u32,
+    ) {
+        payer.require_auth();
+        enter(&env, &asset, &payer, &beneficiary, amount, rounds, false);
+    }
+
+    /// Enter using a SEP-41 allowance: `owner` approves this contract for
+    /// `amount` beforehand and the deposit is pulled with `transfer_from`, so
+    /// the owner's authorization covers only this call, not a nested transfer.
+    pub fn enter_with_allowance(
+        env: Env,
+        asset: Address,
+        owner: Address,
+        beneficiary: Address,
+        amount: i128,
+        rounds: u32,
+    ) {
+        owner.require_auth();
+        enter(&env, &asset, &owner, &beneficiary, amount, rounds, true);
+    }
+
+    /// Enter with any token the swap router can convert into the pool asset.
+    /// The entry is recorded in pool-asset units, after the swap.
+    pub fn enter_with_swap(
+        env: Env,
+        asset: Address,
+        player: Address,
+        token_in: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        rounds: u32,
+    ) {
+        player.require_auth();
+        require_mode(&env, &asset, PoolMode::Rounds);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let router: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::SwapRouter)
+            .unwrap_or_else(|| panic!("Swaps are not enabled"));
+
+        if token_in == asset {
+            panic!("Use enter_lottery for the pool asset");
+        }
+        if amount_in <= 0 || min_amount_out <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        let mut round = open_round_for_entry(&env, &asset);
+
+        // Step 1: Take the input token and swap it into the pool asset
+        env.invoke_contract::<()>(
+            &token_in,
+            &Symbol::new(&env, "transfer"),
+            (player.clone(), env.current_contract_address(), amount_in).into_val(&env),
+        );
+        let amount = swap_to_asset(&env, &router, &token_in, &asset, amount_in, min_amount_out);
+
+        // Step 2: Record the entry in pool-asset units
+        record_entry(&env, &asset, &mut round, &player, amount, rounds);
+        close_if_full(&env, &asset, &mut round);
+        save_round(&env, &asset, &round);
+
+        // Step 3: Supply to the yield strategy
+        strategy_supply(&env, &asset, amount);
+
+        env.events().publish(
+            (symbol_short!("swapped"), asset.clone(), player),
+            (token_in, amount_in, amount, asset_decimals(&env, &asset)),
+        );
+    }
+
+    /// Fund entries for many beneficiaries with a single token transfer and
+    /// a single Blend supply for the total. Takes at most `MAX_BATCH_ENTRIES`
+    /// (15) beneficiaries per call, which keeps the new entries plus the Blend
+    /// supply within one transaction's ledger entry limits; larger groups are
+    /// split over several calls.
+    pub fn enter_batch(env: Env, asset: Address, payer: Address, entries: Vec<(Address, i128)>) {
+        payer.require_auth();
+        require_mode(&env, &asset, PoolMode::Rounds);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if entries.is_empty() {
+            panic!("No entries");
+        }
+        if entries.len() > MAX_BATCH_ENTRIES {
+            panic!("Too many entries in one batch");
+        }
+
+        let mut round = open_round_for_entry(&env, &asset);
+
+        let mut total: i128 = 0;
+        for (beneficiary, amount) in entries.iter() {
+            require_sponsor_consent(&env, &asset, &round, &payer, &beneficiary, 1);
+            record_entry(&env, &asset, &mut round, &beneficiary, amount, 1);
+            total += amount;
+        }
+        close_if_full(&env, &asset, &mut round);
+        save_round(&env, &asset, &round);
+
+        pull_deposit(&env, &asset, &payer, total, false);
+        strategy_supply(&env, &asset, total);
+
+        env.events().publish(
+            (symbol_short!("batch"), asset.clone(), payer),
+            (round.id, entries.len(), total, asset_decimals(&env, &asset)),
+        );
+    }
+
+    /// Withdraw some or all of a deposit while the round is still open.
+    /// A full withdrawal gives up the ticket; the early-exit fee, if any, goes to the prize.
+    /// Returns the position in the withdrawal queue if it has to wait for Blend liquidity.
+    pub fn withdraw_entry(env: Env, asset: Address, player: Address, amount: i128) -> Option<u32> {
+        player.require_auth();
+        require_mode(&env, &asset, PoolMode::Rounds);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let min_deposit: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinDeposit(asset.clone()))
+            .unwrap();
+        let fee_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EarlyExitFee(asset.clone()))
+            .unwrap_or(0);
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        let mut round: Round = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
+
+        if effective_status(&env, &round) != RoundStatus::Open {
+            panic!("Withdrawals are only possible while the round is open");
+        }
+
+        let player_key = DataKey::PlayerDeposit(asset.clone(), current_round_id, player.clone());
+        let mut player_entry: PlayerEntry = env
+            .storage()
+            .persistent()
+            .get(&player_key)
+            .unwrap_or_else(|| panic!("Player not in this round"));
+
+        if amount > player_entry.deposit {
+            panic!("Amount exceeds deposit");
+        }
+        let remaining = player_entry.deposit - amount;
+        if remaining > 0 && remaining < min_deposit {
+            panic!("Remaining deposit below minimum");
+        }
+
+        let fee = amount * fee_bps as i128 / 10000;
+        add_i128(&env, &DataKey::PendingClaims(asset.clone()), amount - fee);
+        let position = pay_out(
+            &env,
+            &asset,
+            &player,
+            amount - fee,
+            amount,
+            current_round_id,
+        );
+
+        round.total_deposits -= amount;
+        if renews(&player_entry) {
+            round.renewing_deposits -= amount;
+        }
+        round.jackpot += fee;
+
+        if remaining > 0 {
+            player_entry.deposit = remaining;
+            env.storage().persistent().set(&player_key, &player_entry);
+            bump(&env, &player_key);
+        } else {
+            // Give up the ticket: move the last player into the freed slot
+            let last_index = round.player_count - 1;
+            let last_key = DataKey::PlayerAt(asset.clone(), current_round_id, last_index);
+            if player_entry.index != last_index {
+                let moved: Address = env.storage().persistent().get(&last_key).unwrap();
+                let moved_key =
+                    DataKey::PlayerDeposit(asset.clone(), current_round_id, moved.clone());
+                let mut moved_entry: PlayerEntry =
+                    env.storage().persistent().get(&moved_key).unwrap();
+                moved_entry.index = player_entry.index;
+                env.storage().persistent().set(&moved_key, &moved_entry);
+                bump(&env, &moved_key);
+
+                let index_key =
+                    DataKey::PlayerAt(asset.clone(), current_round_id, player_entry.index);
+                env.storage().persistent().set(&index_key, &moved);
+                bump(&env, &index_key);
+            }
+            env.storage().persistent().remove(&last_key);
+            env.storage().persistent().remove(&player_key);
+            round.player_count -= 1;
+        }
+
+        save_round(&env, &asset, &round);
+
+        env.events().publish(
+            (symbol_short!("withdrawn"), asset.clone(), player),
+            (current_round_id, amount, fee, asset_decimals(&env, &asset)),
+        );
+
+        position
+    }
+
+    /// Opt in or out of carrying the current round's deposit into the following
+    /// rounds. Renewing deposits stay supplied to Blend between rounds.
+    pub fn set_auto_renew(env: Env, asset: Address, player: Address, auto_renew: bool) {
+        player.require_auth();
+        require_mode(&env, &asset, PoolMode::Rounds);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+
+        let mut round: Round = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
+
+        if effective_status(&env, &round) != RoundStatus::Open {
+            panic!("Round is not open");
+        }
+
+        let player_key = DataKey::PlayerDeposit(asset.clone(), current_round_id, player.clone());
+        let mut player_entry: PlayerEntry = env
+            .storage()
+            .persistent()
+            .get(&player_key)
+            .unwrap_or_else(|| panic!("Player not in this round"));
+
+        if player_entry.auto_renew == auto_renew {
+            return;
+        }
+
+        round.renewing_deposits -= renewing_amount(&player_entry);
+        player_entry.auto_renew = auto_renew;
+        round.renewing_deposits += renewing_amount(&player_entry);
+
+        env.storage().persistent().set(&player_key, &player_entry);
+        bump(&env, &player_key);
+        save_round(&env, &asset, &round);
+    }
+
+    /// Set the fee kept from early withdrawals, in basis points
+    pub fn set_early_exit_fee(env: Env, asset: Address, fee_bps: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        if fee_bps > 10000 {
+            panic!("Fee cannot exceed 100%");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::EarlyExitFee(asset.clone()), &fee_bps);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Choose where deposits earn yield. Only possible while no principal is
+    /// supplied: the current round and savings balances must be empty and
+    /// every refund and prize paid out.
+    pub fn set_yield_strategy(env: Env, asset: Address, strategy: YieldStrategy) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        let round: Round = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
+
+        if round.total_deposits > 0 || savings_totals(&env, &asset).total_balance > 0 {
+            panic!("Pool still holds deposits");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Draw(asset.clone(), current_round_id - 1))
+        {
+            panic!("Previous round is still settling");
+        }
+        let queue = withdrawal_queue(&env, &asset);
+        if queue.head < queue.tail {
+            panic!("Withdrawals are still queued");
+        }
+        if exit_held(&env, &asset).is_some() {
+            panic!("Emergency exit in progress");
+        }
+        // Unclaimed refunds and prizes may still be backed by supplied principal
+        if get_i128(&env, &DataKey::PendingClaims(asset.clone())) > 0
+            || blend_pools(&env, &asset)
+                .iter()
+                .any(|allocation| pool_principal(&env, &asset, &allocation.pool) > 0)
+        {
+            panic!("Principal is still supplied to the yield strategy");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldStrategy(asset.clone()), &strategy);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Set the Blend pools deposits are spread across and their target weights.
+    /// The list order is the order withdrawals draw from. Added pools must list
+    /// the asset; a pool can only be dropped once it holds no principal, and its
+    /// remaining interest goes to the jackpot. Use `rebalance` to move existing
+    /// principal towards the new weights.
+    pub fn set_blend_pools(env: Env, asset: Address, pools: Vec<PoolAllocation>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        let total_weight: u32 = pools.iter().map(|allocation| allocation.weight).sum();
+        if total_weight == 0 {
+            panic!("Pools need a positive total weight");
+        }
+        for (i, allocation) in pools.iter().enumerate() {
+            if pools
+                .iter()
+                .skip(i + 1)
+                .any(|other| other.pool == allocation.pool)
+            {
+                panic!("Duplicate pool");
+            }
+        }
+
+        let previous_pools = blend_pools(&env, &asset);
+        for allocation in pools.iter() {
+            let added = !previous_pools
+                .iter()
+                .any(|previous| previous.pool == allocation.pool);
+            if added {
+                // Panics unless the pool lists the asset
+                let reserve: Reserve = env.invoke_contract(
+                    &allocation.pool,
+                    &Symbol::new(&env, "get_reserve"),
+                    (asset.clone(),).into_val(&env),
+                );
+                if !reserve.config.enabled {
+                    panic!("Pool reserve is disabled");
+                }
+            }
+        }
+
+        // A dropped pool's interest and BLND are collected before it is forgotten
+        let mut harvested = 0;
+        for previous in previous_pools.iter() {
+            let kept = pools
+                .iter()
+                .any(|allocation| allocation.pool == previous.pool);
+            if kept {
+                continue;
+            }
+            if pool_principal(&env, &asset, &previous.pool) > 0 {
+                panic!("Pool still holds principal");
+            }
+            if yield_strategy(&env, &asset) == YieldStrategy::Blend {
+                let interest = blend_position_value(&env, &asset, &previous.pool);
+                if interest > blend_liquidity(&env, &asset, &previous.pool) {
+                    panic!("Pool interest can't be withdrawn yet");
+                }
+                if interest > 0 {
+                    blend_withdraw(&env, &asset, &previous.pool, interest);
+                    harvested += interest;
+                }
+                let claimed = blend_claim_emissions(&env, &asset, &previous.pool);
+                add_i128(&env, &DataKey::UnsoldEmissions(asset.clone()), claimed);
+            }
+        }
+        if harvested > 0 {
+            add_to_jackpot(&env, &asset, harvested);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::BlendPools(asset.clone()), &pools);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Move principal from pools above their target weight to pools below it,
+    /// as far as each pool's liquidity allows. Returns the amount moved.
+    pub fn rebalance(env: Env, asset: Address) -> i128 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        if yield_strategy(&env, &asset) != YieldStrategy::Blend {
+            panic!("Pool is not supplied to Blend");
+        }
+        if exit_held(&env, &asset).is_some() {
+            panic!("Emergency exit in progress");
+        }
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let pools = blend_pools(&env, &asset);
+        let total_weight: u32 = pools.iter().map(|allocation| allocation.weight).sum();
+        let total: i128 = pools
+            .iter()
+            .map(|allocation| pool_principal(&env, &asset, &allocation.pool))
+            .sum();
+
+        // Step 1: Pull the excess out of over-allocated pools
+        let mut moved = 0;
+        for allocation in pools.iter() {
+            let principal = pool_principal(&env, &asset, &allocation.pool);
+            let target = pool_target(&allocation, total, total_weight);
+            if principal > target {
+                let take =
+                    (principal - target).min(blend_liquidity(&env, &asset, &allocation.pool));
+                if take > 0 {
+                    blend_withdraw(&env, &asset, &allocation.pool, take);
+                    add_i128(
+                        &env,
+                        &DataKey::PoolPrincipal(asset.clone(), allocation.pool),
+                        -take,
+                    );
+                    moved += take;
+                }
+            }
+        }
+
+        // Step 2: Supply it to under-allocated pools; rounding leftovers go
+        // to the last pool that received funds
+        let mut remaining = moved;
+        let mut last_pool: Option<Address> = None;
+        for allocation in pools.iter() {
+            let principal = pool_principal(&env, &asset, &allocation.pool);
+            let target = pool_target(&allocation, total, total_weight);
+            let give = (target - principal).min(remaining);
+            if give > 0 {
+                blend_supply(&env, &asset, &allocation.pool, give);
+                add_i128(
+                    &env,
+                    &DataKey::PoolPrincipal(asset.clone(), allocation.pool.clone()),
+                    give,
+                );
+                remaining -= give;
+                last_pool = Some(allocation.pool);
+            }
+        }
+        if remaining > 0 {
+            let pool = last_pool.unwrap_or_else(|| pools.get(0).unwrap().pool);
+            blend_supply(&env, &asset, &pool, remaining);
+            add_i128(
+                &env,
+                &DataKey::PoolPrincipal(asset.clone(), pool),
+                remaining,
+            );
+        }
+
+        env.events().publish(
+            (symbol_short!("rebalance"), asset.clone()),
+            (moved, asset_decimals(&env, &asset)),
+        );
 
-#[contract]
-pub struct LotteryPool;
+        moved
+    }
 
-#[contractimpl]
-impl LotteryPool {
-    /// Initialize lottery pool with Blend integration
-    pub fn initialize(
-        env: Env,
-        admin: Address,
-        usdc_token: Address,
-        blend_pool: Address, // Blend pool address
-        yield_rate: u32,
-        round_duration: u64,
-        min_deposit: i128,
-    ) {
-        if is_initialized(&env) {
-            panic!("Contract already initialized");
+    /// Principal and current value held in each configured Blend pool
+    pub fn get_pool_exposure(env: Env, asset: Address) -> Vec<PoolExposure> {
+        let mut exposure = Vec::new(&env);
+        for allocation in blend_pools(&env, &asset).iter() {
+            exposure.push_back(PoolExposure {
+                principal: pool_principal(&env, &asset, &allocation.pool),
+                value: blend_position_value(&env, &asset, &allocation.pool),
+                pool: allocation.pool,
+                weight: allocation.weight,
+            });
         }
+        exposure
+    }
 
+    /// Claim the BLND emissions earned by the pool's Blend positions, swap them
+    /// into the pool asset and add the result to the current round's jackpot
+    pub fn harvest_emissions(env: Env, asset: Address, min_amount_out: i128) -> i128 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
+        require_asset(&env, &asset);
 
-        if yield_rate > 10000 {
-            panic!("Yield rate cannot exceed 100%");
+        if yield_strategy(&env, &asset) != YieldStrategy::Blend {
+            panic!("Pool is not supplied to Blend");
         }
-        if min_deposit <= 0 {
-            panic!("Minimum deposit must be positive");
+
+        let emission_token: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::EmissionToken)
+            .unwrap_or_else(|| panic!("Emission token not set"));
+        let router: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::SwapRouter)
+            .unwrap_or_else(|| panic!("Swaps are not enabled"));
+
+        // Includes BLND claimed from pools dropped by `set_blend_pools`; other
+        // sub-pools' BLND stays with them
+        let unsold = DataKey::UnsoldEmissions(asset.clone());
+        let mut claimed = get_i128(&env, &unsold);
+        for allocation in blend_pools(&env, &asset).iter() {
+            claimed += blend_claim_emissions(&env, &asset, &allocation.pool);
         }
-        if round_duration < 60 {
-            panic!("Round duration must be at least 60 seconds");
+        if claimed == 0 {
+            return 0;
         }
+        env.storage().persistent().remove(&unsold);
+
+        let amount = swap_to_asset(
+            &env,
+            &router,
+            &emission_token,
+            &asset,
+            claimed,
+            min_amount_out,
+        );
+        add_i128(&env, &DataKey::TotalEmissions(asset.clone()), amount);
+        add_to_jackpot(&env, &asset, amount);
+
+        env.events().publish(
+            (symbol_short!("emissions"), asset.clone()),
+            (claimed, amount, asset_decimals(&env, &asset)),
+        );
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        amount
+    }
+
+    /// Set the token Blend pays emissions in (BLND)
+    pub fn set_emission_token(env: Env, token: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
 
-        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage()
             .instance()
-            .set(&DataKey::UsdcToken, &usdc_token);
+            .set(&DataKey::EmissionToken, &token);
+
         env.storage()
             .instance()
-            .set(&DataKey::BlendPool, &blend_pool);
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Enable deposits in other assets through a Soroswap-style router
+    pub fn set_swap_router(env: Env, router: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::SwapRouter, &router);
+
         env.storage()
             .instance()
-            .set(&DataKey::YieldRate, &yield_rate);
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Cap each player's deposit, a round's total deposits and its number of
+    /// players (0 = no limit). With `close_when_full`, a round that reaches a
+    /// cap stops taking entries and can be drawn straight away. Deposits carried
+    /// over by auto-renewal are never refused: they count towards the caps but
+    /// can take a round past them.
+    pub fn set_deposit_caps(
+        env: Env,
+        asset: Address,
+        max_per_player: i128,
+        max_round_deposits: i128,
+        max_players: u32,
+        close_when_full: bool,
+    ) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        if max_per_player < 0 || max_round_deposits < 0 {
+            panic!("Caps cannot be negative");
+        }
+
+        env.storage().instance().set(
+            &DataKey::MaxDepositPerPlayer(asset.clone()),
+            &max_per_player,
+        );
+        env.storage().instance().set(
+            &DataKey::MaxRoundDeposits(asset.clone()),
+            &max_round_deposits,
+        );
         env.storage()
             .instance()
-            .set(&DataKey::RoundDuration, &round_duration);
+            .set(&DataKey::MaxPlayersPerRound(asset.clone()), &max_players);
         env.storage()
             .instance()
-            .set(&DataKey::MinDeposit, &min_deposit);
+            .set(&DataKey::CloseWhenFull(asset.clone()), &close_when_full);
 
-        // Initialize global stats
-        env.storage()
-            .persistent()
-            .set(&DataKey::TotalVolume, &0i128);
         env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Switch between per-round entries and the savings model. Only possible
+    /// while the current round is empty and no savings balance is held.
+    pub fn set_pool_mode(env: Env, asset: Address, mode: PoolMode) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        let round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::TotalPlayers, &0u32);
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
 
-        let current_time = env.ledger().timestamp();
-        let round = Round {
-            id: 1,
-            start_time: current_time,
-            end_time: current_time + round_duration,
-            total_deposits: 0,
-            total_yield: 0,
-            winner: None,
-            is_active: true,
-            player_count: 0,
-        };
+        if round.player_count > 0 || savings_totals(&env, &asset).total_balance > 0 {
+            panic!("Pool still holds deposits");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Draw(asset.clone(), current_round_id - 1))
+        {
+            panic!("Previous round is still settling");
+        }
 
-        env.storage().instance().set(&DataKey::CurrentRound, &1u32);
-        env.storage().persistent().set(&DataKey::Round(1), &round);
         env.storage()
-            .persistent()
-            .set(&DataKey::PlayerList(1), &Vec::<Address>::new(&env));
+            .instance()
+            .set(&DataKey::Mode(asset.clone()), &mode);
+        restart_savings_twab(&env, &asset);
 
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
     }
 
-    /// Enter lottery - deposits USDC and stakes in Blend for yield
-    pub fn enter_lottery(env: Env, player: Address, amount: i128) {
+    /// Savings mode: add to a standing balance that stays supplied to Blend
+    /// and takes part in every draw until withdrawn
+    pub fn deposit(env: Env, asset: Address, player: Address, amount: i128) {
         player.require_auth();
+        require_mode(&env, &asset, PoolMode::Savings);
 
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let min_deposit: i128 = env.storage().instance().get(&DataKey::MinDeposit).unwrap();
-        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
-        let blend_pool: Address = env.storage().instance().get(&DataKey::BlendPool).unwrap();
+        let min_deposit: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinDeposit(asset.clone()))
+            .unwrap();
         let current_round_id: u32 = env
             .storage()
             .instance()
-            .get(&DataKey::CurrentRound)
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        let current_round: Round = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Round(asset.clone(), current_round_id))
             .unwrap();
 
-        if amount < min_deposit {
+        if amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        let mut account = load_account(&env, &asset, &player, &current_round);
+        if account.balance + amount < min_deposit {
             panic!("Deposit below minimum");
         }
-        if amount < 0 {
-            panic!("Amount must be positive");
+
+        env.invoke_contract::<()>(
+            &asset,
+            &Symbol::new(&env, "transfer"),
+            (player.clone(), env.current_contract_address(), amount).into_val(&env),
+        );
+        strategy_supply(&env, &asset, amount);
+
+        let mut totals = savings_totals(&env, &asset);
+        accrue_totals(&env, &mut totals);
+        totals.total_balance += amount;
+        save_savings_totals(&env, &asset, &totals);
+
+        if account.balance == 0 {
+            let total_players: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::TotalPlayers(asset.clone()))
+                .unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&DataKey::TotalPlayers(asset.clone()), &(total_players + 1));
+            bump(&env, &DataKey::TotalPlayers(asset.clone()));
         }
+        account.balance += amount;
+        save_account(&env, &asset, &account);
+        add_i128(&env, &DataKey::TotalVolume(asset.clone()), amount);
 
-        let mut round: Round = env
+        env.events().publish(
+            (symbol_short!("deposit"), asset.clone(), player),
+            (amount, account.balance, asset_decimals(&env, &asset)),
+        );
+    }
+
+    /// Savings mode: withdraw from a standing balance at any time.
+    /// Returns the position in the withdrawal queue if it has to wait for Blend liquidity.
+    pub fn withdraw(env: Env, asset: Address, player: Address, amount: i128) -> Option<u32> {
+        player.require_auth();
+        require_mode(&env, &asset, PoolMode::Savings);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let min_deposit: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinDeposit(asset.clone()))
+            .unwrap();
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        let current_round: Round = env
             .storage()
             .persistent()
-            .get(&DataKey::Round(current_round_id))
+            .get(&DataKey::Round(asset.clone(), current_round_id))
             .unwrap();
 
-        if !round.is_active {
-            panic!("Round is not active");
+        if amount <= 0 {
+            panic!("Amount must be positive");
         }
-
-        let current_time = env.ledger().timestamp();
-        if current_time >= round.end_time {
-            panic!("Round has ended, please wait for winner selection");
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Account(asset.clone(), player.clone()))
+        {
+            panic!("No savings balance");
         }
 
-        let player_key = DataKey::PlayerDeposit(current_round_id, player.clone());
-        if env.storage().persistent().has(&player_key) {
-            panic!("Player already entered this round");
+        let mut account = load_account(&env, &asset, &player, &current_round);
+        if amount > account.balance {
+            panic!("Amount exceeds balance");
+        }
+        let remaining = account.balance - amount;
+        if remaining > 0 && remaining < min_deposit {
+            panic!("Remaining balance below minimum");
         }
 
-        // Step 1: Transfer USDC from player to lottery contract
-        env.invoke_contract::<()>(
-            &usdc_token,
-            &Symbol::new(&env, "transfer"),
-            (player.clone(), env.current_contract_address(), amount).into_val(&env),
+        let mut totals = savings_totals(&env, &asset);
+        accrue_totals(&env, &mut totals);
+        totals.total_balance -= amount;
+        save_savings_totals(&env, &asset, &totals);
+
+        account.balance = remaining;
+        save_account(&env, &asset, &account);
+
+        add_i128(&env, &DataKey::PendingClaims(asset.clone()), amount);
+        let position = pay_out(&env, &asset, &player, amount, amount, current_round_id);
+
+        env.events().publish(
+            (symbol_short!("withdraw"), asset.clone(), player),
+            (amount, remaining, asset_decimals(&env, &asset)),
         );
 
-        // Step 2: Authorize lottery contract for nested calls
-        // This authorizes Blend to call token.transfer on behalf of lottery contract
-        env.authorize_as_current_contract(vec![
-            &env,
-            InvokerContractAuthEntry::Contract(SubContractInvocation {
-                context: ContractContext {
-                    contract: usdc_token.clone(),
-                    fn_name: Symbol::new(&env, "transfer"),
-                    args: (env.current_contract_address(), blend_pool.clone(), amount)
-                        .into_val(&env),
-                },
-                sub_invocations: vec![&env],
-            }),
-        ]);
+        position
+    }
 
-        // Step 3: Deposit to Blend pool as SupplyCollateral
-        let supply_request = Request {
-            request_type: 2, // SupplyCollateral = 2 (earns yield!)
-            address: usdc_token.clone(),
-            amount: amount,
-        };
+    pub fn get_account(env: Env, asset: Address, player: Address) -> Option<SavingsAccount> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Account(asset.clone(), player))
+    }
 
-        let requests = Vec::from_array(&env, [supply_request]);
+    /// Current round, with `status` reporting `AwaitingDraw` once the round has ended
+    pub fn get_current_round(env: Env, asset: Address) -> Round {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        // Call Blend's submit
-        let _ = env.invoke_contract::<soroban_sdk::Val>(
-            &blend_pool,
-            &Symbol::new(&env, "submit"),
-            (
-                env.current_contract_address(),
-                env.current_contract_address(),
-                env.current_contract_address(),
-                requests,
-            )
-                .into_val(&env),
-        );
-
-        // Store player entry
-        let player_entry = PlayerEntry {
-            player: player.clone(),
-            deposit: amount,
-            round_id: current_round_id,
-            has_claimed: false,
-        };
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or(0);
 
-        env.storage().persistent().set(&player_key, &player_entry);
+        if current_round_id == 0 {
+            panic!("Contract not initialized");
+        }
 
-        let mut players: Vec<Address> = env
+        let mut round: Round = env
             .storage()
             .persistent()
-            .get(&DataKey::PlayerList(current_round_id))
-            .unwrap_or(Vec::new(&env));
-        players.push_back(player.clone());
-        env.storage()
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
+        round.status = effective_status(&env, &round);
+        round
+    }
+
+    /// Remaining deposit and player capacity of the current round
+    pub fn get_current_capacity(env: Env, asset: Address) -> RoundCapacity {
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or(0);
+
+        if current_round_id == 0 {
+            panic!("Contract not initialized");
+        }
+
+        let round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::PlayerList(current_round_id), &players);
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
+        round_capacity(&env, &asset, &round)
+    }
 
-        round.total_deposits += amount;
-        round.player_count += 1;
+    /// Start the draw - harvests Blend interest, fixes the yield and the winning
+    /// ticket, opens the next round and settles the first `limit` players
+    /// (0 walks none). Returns the winner if settlement finished within this
+    /// call; otherwise `settle_batch` continues it. Rounds with too few players
+    /// roll over. Principal stays in Blend; auto-renewing deposits are carried
+    /// into the next round.
+    pub fn pick_winner(env: Env, asset: Address, limit: u32) -> Option<Address> {
         env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let current_round_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+
+        let mut round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::Round(current_round_id), &round);
+            .get(&DataKey::Round(asset.clone(), current_round_id))
+            .unwrap();
 
-        // Update global stats
-        let total_vol: i128 = env
+        // Check round has ended
+        match effective_status(&env, &round) {
+            RoundStatus::AwaitingDraw => {}
+            RoundStatus::Open => panic!("Round has not ended yet"),
+            _ => panic!("Round already finished"),
+        }
+        if env
             .storage()
             .persistent()
-            .get(&DataKey::TotalVolume)
-            .unwrap_or(0);
+            .has(&DataKey::Draw(asset.clone(), current_round_id - 1))
+        {
+            panic!("Previous round is still settling");
+        }
+        transition(&env, &mut round, RoundStatus::Drawing);
+
+        if pool_mode(&env, &asset) == PoolMode::Savings {
+            return draw_savings(&env, &asset, round, limit);
+        }
+
+        if round.player_count == 0 {
+            panic!("No players in this round");
+        }
+
+        // Calculate real yield from the strategy
+        // Step 1: Harvest accrued interest; principal stays supplied across
+        // rounds and is withdrawn as refunds are claimed
+        let interest = strategy_harvest(&env, &asset);
+
+        // Step 2: Check balance after withdrawal
+        let balance_after: i128 = env.invoke_contract(
+            &asset,
+            &Symbol::new(&env, "balance"),
+            (env.current_contract_address(),).into_val(&env),
+        );
+
+        // Calculate yield safely
+        // balance_after = house_money + jackpot + interest
+        //               + principal the strategy keeps in the contract
+        //               + claims owed from earlier rounds + swept funds
+        // available_for_yield = everything that isn't owed to someone
+        let available_for_yield = balance_after
+            - interest
+            - idle_principal(&env, &asset, round.total_deposits)
+            - round.jackpot
+            - get_i128(&env, &DataKey::PendingClaims(asset.clone()))
+            - get_i128(&env, &DataKey::UnclaimedFunds(asset.clone()));
+        let total_yield = draw_yield(
+            &env,
+            &asset,
+            round.total_deposits,
+            available_for_yield,
+            interest,
+        );
+
+        round.total_yield = total_yield;
+
+        // Fix the winning ticket now; settlement walks the players to find its owner.
+        // Each unit deposited is one ticket.
+        round.seed = env.ledger().timestamp() ^ (env.ledger().sequence() as u64);
+        let mut draw = DrawState {
+            rollover: round.player_count < 3,
+            winning_ticket: round.seed as i128 % round.total_deposits,
+            cursor: 0,
+            tickets_seen: 0,
+            winner: None,
+        };
+
+        save_round(&env, &asset, &round);
+
+        // Players can keep entering while this round settles
+        if draw.rollover {
+            // Not enough players: yield rolls to the next round as jackpot
+            let jackpot = round.jackpot + total_yield;
+            let new_round_id = start_next_round(&env, &asset, current_round_id, jackpot);
+            env.events().publish(
+                (symbol_short!("jackpot"), asset.clone(), new_round_id),
+                (jackpot, asset_decimals(&env, &asset)),
+            );
+        } else {
+            start_next_round(&env, &asset, current_round_id, 0);
+        }
+
+        settle(&env, &asset, round, &mut draw, limit)
+    }
+
+    /// Continue settling a drawn round from `cursor`, walking at most `limit`
+    /// players. Returns the cursor to resume from; the round is settled once
+    /// the cursor reaches its player count. Each player walked reads two
+    /// entries and a renewing one writes two more into the next round, so
+    /// about 20 players fit in one transaction's ledger entry limits.
+    pub fn settle_batch(env: Env, asset: Address, round_id: u32, cursor: u32, limit: u32) -> u32 {
         env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::TotalVolume, &(total_vol + amount));
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
 
-        let total_players: u32 = env
+        if round.status != RoundStatus::Drawing {
+            panic!("Round is not being settled");
+        }
+
+        let mut draw: DrawState = env
             .storage()
             .persistent()
-            .get(&DataKey::TotalPlayers)
-            .unwrap_or(0);
+            .get(&DataKey::Draw(asset.clone(), round_id))
+            .unwrap();
+
+        if cursor != draw.cursor {
+            panic!("Cursor does not match settlement progress");
+        }
+        if limit == 0 {
+            panic!("Limit must be positive");
+        }
+
+        match pool_mode(&env, &asset) {
+            PoolMode::Rounds => settle(&env, &asset, round, &mut draw, limit),
+            PoolMode::Savings => settle_savings(&env, &asset, round, &mut draw, limit),
+        };
+        draw.cursor
+    }
+
+    /// Winner collects the prize recorded by `pick_winner`. Returns the
+    /// position in the withdrawal queue if it has to wait for Blend liquidity.
+    pub fn claim_prize(env: Env, asset: Address, round_id: u32) -> Option<u32> {
         env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::TotalPlayers, &(total_players + 1));
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
+
+        if round.status != RoundStatus::Settled {
+            panic!("Round has no prize to claim");
+        }
+
+        let winner = round.winner.clone().unwrap();
+        winner.require_auth();
+        let prize = round.prize;
+
+        if round.prize_claimed {
+            panic!("Prize already claimed");
+        }
+
+        // The winner's own deposit, if part of the prize, is still supplied
+        let principal = prize - round.total_yield - round.jackpot;
+        round.prize_claimed = true;
+        let position = pay_claim(&env, &asset, &mut round, &winner, prize, principal);
+
+        add_i128(&env, &DataKey::TotalPrizesPaid(asset.clone()), prize);
 
-        // Emit event
         env.events().publish(
-            (symbol_short!("entered"), player.clone()),
-            (current_round_id, amount),
+            (symbol_short!("prize"), asset.clone(), winner),
+            (round_id, prize, asset_decimals(&env, &asset)),
         );
+
+        position
+    }
+
+    /// Set how long players have to claim refunds and prizes (0 disables the
+    /// deadline). Applies to rounds settled or cancelled after the change.
+    pub fn set_claim_window(env: Env, asset: Address, claim_window: u64) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ClaimWindow(asset.clone()), &claim_window);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
     }
 
-    pub fn get_current_round(env: Env) -> Round {
+    /// Cancel an unsettled round: every entry becomes refundable via
+    /// `claim_refund` and a fresh round is opened. The round's principal is
+    /// withdrawn from the strategy right away; if liquidity is short it stays
+    /// supplied and refunds join the withdrawal queue as they are claimed.
+    pub fn cancel_round(env: Env, asset: Address, round_id: u32, reason: String) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -301,20 +2942,66 @@ impl LotteryPool {
         let current_round_id: u32 = env
             .storage()
             .instance()
-            .get(&DataKey::CurrentRound)
-            .unwrap_or(0);
-
-        if current_round_id == 0 {
-            panic!("Contract not initialized");
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        if round_id != current_round_id {
+            panic!("Only the current round can be cancelled");
+        }
+        // Auto-renewing deposits may still be carried into this round
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Draw(asset.clone(), round_id - 1))
+        {
+            panic!("Previous round is still settling");
         }
 
-        env.storage()
+        let mut round: Round = env
+            .storage()
             .persistent()
-            .get(&DataKey::Round(current_round_id))
-            .unwrap()
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
+
+        transition(&env, &mut round, RoundStatus::Cancelled);
+        if pool_mode(&env, &asset) == PoolMode::Savings {
+            // The cancelled round's time-weighted balances are never drawn
+            restart_savings_twab(&env, &asset);
+        }
+        let owed = round.total_deposits;
+        let queue = withdrawal_queue(&env, &asset);
+        // Claims already waiting in the queue get the liquidity first
+        let principal = if queue.head == queue.tail && strategy_liquidity(&env, &asset) >= owed {
+            strategy_withdraw(&env, &asset, owed);
+            0
+        } else {
+            owed
+        };
+        open_claims(&env, &asset, &mut round, owed, principal);
+
+        save_round(&env, &asset, &round);
+
+        env.events().publish(
+            (symbol_short!("cancelled"), asset.clone(), round_id),
+            reason,
+        );
+
+        // Any carried jackpot moves on to the fresh round
+        start_next_round(&env, &asset, round_id, round.jackpot);
     }
-    /// Pick winner - withdraws from Blend and distributes yield
-    pub fn pick_winner(env: Env) -> Address {
+
+    /// Pull out of Blend: accrued interest and all the principal the pools can
+    /// release now come back to the contract. Principal still lent out stays
+    /// tracked and is drained by later `process_withdrawals` calls, which pay
+    /// the claims queued for it; new deposits stay in the contract meanwhile.
+    /// Once nothing is left in Blend the pool falls back to `HouseFunded`.
+    /// The current round ends as `EmergencyExited` with every entry refundable
+    /// via `claim_refund`; savings balances stay withdrawable. The interest
+    /// joins the jackpot of the fresh round.
+    pub fn emergency_exit(env: Env, asset: Address, reason: String) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -322,341 +3009,597 @@ impl LotteryPool {
         let current_round_id: u32 = env
             .storage()
             .instance()
-            .get(&DataKey::CurrentRound)
-            .unwrap();
-
-        let blend_pool: Address = env.storage().instance().get(&DataKey::BlendPool).unwrap();
-        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+            .get(&DataKey::CurrentRound(asset.clone()))
+            .unwrap_or_else(|| panic!("No pool for this asset"));
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Draw(asset.clone(), current_round_id - 1))
+        {
+            panic!("Previous round is still settling");
+        }
 
         let mut round: Round = env
             .storage()
             .persistent()
-            .get(&DataKey::Round(current_round_id))
+            .get(&DataKey::Round(asset.clone(), current_round_id))
             .unwrap();
+        transition(&env, &mut round, RoundStatus::EmergencyExited);
 
-        // Check round has ended
-        let current_time = env.ledger().timestamp();
-        if current_time < round.end_time {
-            panic!("Round has not ended yet");
-        }
+        let interest = strategy_harvest(&env, &asset);
+        let principal = if yield_strategy(&env, &asset) == YieldStrategy::Blend {
+            drain_blend(&env, &asset)
+        } else {
+            0
+        };
 
-        if !round.is_active {
-            panic!("Round already finished");
+        if pool_mode(&env, &asset) == PoolMode::Savings {
+            restart_savings_twab(&env, &asset);
         }
+        let owed = round.total_deposits;
+        // Refunds come out of the exit's holdings, or queue for the rest
+        let refund_principal = if yield_strategy(&env, &asset) == YieldStrategy::Blend {
+            owed
+        } else {
+            0
+        };
+        open_claims(&env, &asset, &mut round, owed, refund_principal);
+        save_round(&env, &asset, &round);
+
+        env.events().publish(
+            (symbol_short!("emergency"), asset.clone(), current_round_id),
+            (reason, principal, interest, asset_decimals(&env, &asset)),
+        );
+
+        start_next_round(&env, &asset, current_round_id, round.jackpot + interest);
+    }
+
+    /// Claim refund for non-winners, or for any player of a rolled-over, cancelled
+    /// or emergency-exited round.
+    /// Returns the position in the withdrawal queue if it has to wait for Blend liquidity.
+    pub fn claim_refund(env: Env, asset: Address, player: Address, round_id: u32) -> Option<u32> {
+        player.require_auth();
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        // Get player list
-        let players: Vec<Address> = env
+        let mut round: Round = env
             .storage()
             .persistent()
-            .get(&DataKey::PlayerList(current_round_id))
-            .unwrap();
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
 
-        if players.is_empty() {
-            panic!("No players in this round");
+        match round.status {
+            RoundStatus::Settled => {
+                let winner = round
+                    .winner
+                    .as_ref()
+                    .unwrap_or_else(|| panic!("No winner selected yet"));
+
+                if winner == &player {
+                    panic!("Winner cannot claim refund");
+                }
+            }
+            RoundStatus::RolledOver | RoundStatus::Cancelled | RoundStatus::EmergencyExited => {}
+            RoundStatus::Open | RoundStatus::Drawing => panic!("Round is still active"),
+            _ => panic!("Round has no refunds to claim"),
         }
 
-        // Calculate real yield from Blend
-        // Step 1: Check balance before withdrawal (includes house money)
-        let balance_before: i128 = env.invoke_contract(
-            &usdc_token,
-            &Symbol::new(&env, "balance"),
-            (env.current_contract_address(),).into_val(&env),
+        let player_key = DataKey::PlayerDeposit(asset.clone(), round_id, player.clone());
+        let mut player_entry: PlayerEntry = env
+            .storage()
+            .persistent()
+            .get(&player_key)
+            .unwrap_or_else(|| panic!("Player not in this round"));
+
+        let ended_early = matches!(
+            round.status,
+            RoundStatus::Cancelled | RoundStatus::EmergencyExited
         );
+        if renews(&player_entry) && !ended_early {
+            panic!("Deposit was carried into the next round");
+        }
 
-        // Step 2: Authorize withdrawal (Blend will transfer USDC back to lottery)
-        env.authorize_as_current_contract(vec![
-            &env,
-            InvokerContractAuthEntry::Contract(SubContractInvocation {
-                context: ContractContext {
-                    contract: usdc_token.clone(),
-                    fn_name: Symbol::new(&env, "transfer"),
-                    args: (
-                        blend_pool.clone(),
-                        env.current_contract_address(),
-                        round.total_deposits,
-                    )
-                        .into_val(&env),
-                },
-                sub_invocations: vec![&env],
-            }),
-        ]);
+        if player_entry.has_claimed {
+            panic!("Refund already claimed");
+        }
 
-        // Step 3: Withdraw from Blend pool (WithdrawCollateral)
-        let withdraw_request = Request {
-            request_type: 3, // WithdrawCollateral = 3
-            address: usdc_token.clone(),
-            amount: round.total_deposits,
-        };
+        // Mark as claimed; the write also bumps an entry that was just restored from archive
+        player_entry.has_claimed = true;
+        env.storage().persistent().set(&player_key, &player_entry);
+        bump(&env, &player_key);
 
-        let requests = Vec::from_array(&env, [withdraw_request]);
+        // Transfer refund; a cancelled round's deposits may already be back
+        let deposit = player_entry.deposit;
+        let principal = deposit.min(round.principal_outstanding);
+        let position = pay_claim(&env, &asset, &mut round, &player, deposit, principal);
 
-        // Call Blend's submit to withdraw
-        let _ = env.invoke_contract::<soroban_sdk::Val>(
-            &blend_pool,
-            &Symbol::new(&env, "submit"),
-            (
-                env.current_contract_address(),
-                env.current_contract_address(),
-                env.current_contract_address(),
-                requests,
-            )
-                .into_val(&env),
+        // Emit event
+        env.events().publish(
+            (symbol_short!("refund"), asset.clone(), player.clone()),
+            (round_id, player_entry.deposit, asset_decimals(&env, &asset)),
         );
 
-        // Step 4: Check balance after withdrawal
-        let balance_after: i128 = env.invoke_contract(
-            &usdc_token,
-            &Symbol::new(&env, "balance"),
-            (env.current_contract_address(),).into_val(&env),
-        );
+        position
+    }
 
-        // Calculate yield safely
-        // balance_after = house_money + withdrawn_from_blend
-        // available_for_yield = total_balance - deposits_to_refund
-        let available_for_yield = balance_after - round.total_deposits;
-
-        // Use conservative yield: 50% of available (keeps house money for future rounds)
-        // This ensures we always have enough for payouts
-        let total_yield = if available_for_yield > 0 {
-            available_for_yield / 20 // 50% of available becomes yield
-        } else {
-            0 // Safety: no yield if somehow we're short
-        };
+    /// Pay queued claims in order while the strategy has liquidity, at most
+    /// `limit` of them. Anyone can call this. A claim whose transfer fails,
+    /// e.g. to a frozen trustline, is parked for its recipient to collect with
+    /// `claim_parked` so it can't hold up the claims behind it. During an
+    /// emergency exit it then withdraws whatever else Blend can release.
+    /// Returns how many claims left the queue.
+    pub fn process_withdrawals(env: Env, asset: Address, limit: u32) -> u32 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        round.total_yield = total_yield;
+        let mut queue = withdrawal_queue(&env, &asset);
+        let mut liquidity = strategy_liquidity(&env, &asset);
 
-        // Jackpot rollover if less than 3 players
-        if players.len() < 3 {
-            round.is_active = false;
-
-            // Refund all players
-            for player in players.iter() {
-                let entry: PlayerEntry = env
-                    .storage()
-                    .persistent()
-                    .get(&DataKey::PlayerDeposit(current_round_id, player.clone()))
-                    .unwrap();
-
-                env.invoke_contract::<()>(
-                    &usdc_token,
-                    &Symbol::new(&env, "transfer"),
-                    (env.current_contract_address(), player, entry.deposit).into_val(&env),
-                );
+        let mut processed = 0;
+        while processed < limit && queue.head < queue.tail {
+            let key = DataKey::Queued(asset.clone(), queue.head);
+            let entry: QueuedWithdrawal = env.storage().persistent().get(&key).unwrap();
+            if entry.principal > liquidity {
+                break;
             }
 
-            env.storage()
-                .persistent()
-                .set(&DataKey::Round(current_round_id), &round);
+            strategy_withdraw(&env, &asset, entry.principal);
+            liquidity -= entry.principal;
+            let sent = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                &asset,
+                &Symbol::new(&env, "transfer"),
+                (
+                    env.current_contract_address(),
+                    entry.recipient.clone(),
+                    entry.amount,
+                )
+                    .into_val(&env),
+            );
 
-            // Start new round with rolled over yield
-            let round_duration: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::RoundDuration)
-                .unwrap();
-            let new_round_id = current_round_id + 1;
-            let new_round = Round {
-                id: new_round_id,
-                start_time: current_time,
-                end_time: current_time + round_duration,
-                total_deposits: total_yield,
-                total_yield: 0,
-                winner: None,
-                is_active: true,
-                player_count: 0,
-            };
+            env.storage().persistent().remove(&key);
+            queue.head += 1;
+            queue.total -= entry.amount;
+            processed += 1;
 
-            env.storage()
-                .instance()
-                .set(&DataKey::CurrentRound, &new_round_id);
-            env.storage()
-                .persistent()
-                .set(&DataKey::Round(new_round_id), &new_round);
-            env.storage().persistent().set(
-                &DataKey::PlayerList(new_round_id),
-                &Vec::<Address>::new(&env),
-            );
+            if matches!(sent, Ok(Ok(()))) {
+                add_i128(&env, &DataKey::PendingClaims(asset.clone()), -entry.amount);
+                env.events().publish(
+                    (symbol_short!("dequeued"), asset.clone(), entry.recipient),
+                    (entry.round_id, entry.amount, asset_decimals(&env, &asset)),
+                );
+            } else {
+                // Stays a pending claim, now held by the contract
+                add_i128(
+                    &env,
+                    &DataKey::Parked(asset.clone(), entry.recipient.clone()),
+                    entry.amount,
+                );
+                env.events().publish(
+                    (symbol_short!("parked"), asset.clone(), entry.recipient),
+                    (entry.round_id, entry.amount, asset_decimals(&env, &asset)),
+                );
+            }
+        }
 
-            env.events()
-                .publish((symbol_short!("jackpot"), new_round_id), total_yield);
+        env.storage()
+            .persistent()
+            .set(&DataKey::QueueHead(asset.clone()), &queue.head);
+        bump(&env, &DataKey::QueueHead(asset.clone()));
+        env.storage()
+            .persistent()
+            .set(&DataKey::QueuedTotal(asset.clone()), &queue.total);
+        bump(&env, &DataKey::QueuedTotal(asset.clone()));
 
-            panic!("Not enough players! Yield rolled to next round as JACKPOT!");
+        if exit_held(&env, &asset).is_some() {
+            drain_blend(&env, &asset);
         }
 
-        // Select random winner
-        let seed = env.ledger().timestamp() ^ (env.ledger().sequence() as u64);
-        let winner_index = (seed % players.len() as u64) as u32;
-        let winner = players.get(winner_index).unwrap();
+        processed
+    }
 
-        round.winner = Some(winner.clone());
-        round.is_active = false;
+    /// Collect queued claims that `process_withdrawals` couldn't send to
+    /// `recipient`, paying them to `to`. Returns the amount paid.
+    pub fn claim_parked(env: Env, asset: Address, recipient: Address, to: Address) -> i128 {
+        recipient.require_auth();
 
-        // Get winner's deposit
-        let winner_entry: PlayerEntry = env
-            .storage()
-            .persistent()
-            .get(&DataKey::PlayerDeposit(current_round_id, winner.clone()))
-            .unwrap();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        // Transfer prize (original deposit + yield)
-        let prize = winner_entry.deposit + total_yield;
+        let key = DataKey::Parked(asset.clone(), recipient.clone());
+        let amount = get_i128(&env, &key);
+        if amount == 0 {
+            panic!("Nothing parked for this recipient");
+        }
+        env.storage().persistent().remove(&key);
+        add_i128(&env, &DataKey::PendingClaims(asset.clone()), -amount);
 
         env.invoke_contract::<()>(
-            &usdc_token,
+            &asset,
             &Symbol::new(&env, "transfer"),
-            (env.current_contract_address(), winner.clone(), prize).into_val(&env),
+            (env.current_contract_address(), to.clone(), amount).into_val(&env),
+        );
+
+        env.events().publish(
+            (symbol_short!("unparked"), asset.clone(), recipient),
+            (to, amount, asset_decimals(&env, &asset)),
         );
 
-        // Update round
+        amount
+    }
+
+    /// Queued claims parked for `recipient` after a failed transfer
+    pub fn get_parked(env: Env, asset: Address, recipient: Address) -> i128 {
+        get_i128(&env, &DataKey::Parked(asset.clone(), recipient))
+    }
+
+    /// Claims waiting for liquidity occupy positions head..tail
+    pub fn get_withdrawal_queue(env: Env, asset: Address) -> WithdrawalQueue {
+        withdrawal_queue(&env, &asset)
+    }
+
+    pub fn get_queued_withdrawal(env: Env, asset: Address, index: u32) -> Option<QueuedWithdrawal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Queued(asset.clone(), index))
+    }
+
+    /// Move a round's expired refunds and prize into the unclaimed bucket.
+    /// Anyone can call this once the claim deadline has passed.
+    pub fn sweep_unclaimed(env: Env, asset: Address, round_id: u32) -> i128 {
         env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut round: Round = env
+            .storage()
             .persistent()
-            .set(&DataKey::Round(current_round_id), &round);
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
+
+        if round.claim_deadline == 0 || env.ledger().timestamp() <= round.claim_deadline {
+            panic!("Claim window has not expired");
+        }
+        if round.outstanding == 0 {
+            panic!("Nothing left to sweep");
+        }
+        // Queued claims are first in line for whatever Blend can release
+        let queue = withdrawal_queue(&env, &asset);
+        if round.principal_outstanding > 0 && queue.head < queue.tail {
+            panic!("Withdrawals are still queued");
+        }
+
+        // Expired refunds come back from the strategy to be held by the contract
+        if round.principal_outstanding > 0 {
+            strategy_withdraw(&env, &asset, round.principal_outstanding);
+            round.principal_outstanding = 0;
+        }
+
+        let amount = round.outstanding;
+        round.outstanding = 0;
+        save_round(&env, &asset, &round);
+        add_i128(&env, &DataKey::PendingClaims(asset.clone()), -amount);
 
-        // Emit event
         env.events().publish(
-            (symbol_short!("winner"), winner.clone()),
-            (current_round_id, prize),
+            (symbol_short!("swept"), asset.clone(), round_id),
+            (amount, asset_decimals(&env, &asset)),
         );
 
-        // Start new round
-        let round_duration: u64 = env
+        let policy: UnclaimedPolicy = env
             .storage()
             .instance()
-            .get(&DataKey::RoundDuration)
-            .unwrap();
-        let new_round_id = current_round_id + 1;
-        let new_round = Round {
-            id: new_round_id,
-            start_time: current_time,
-            end_time: current_time + round_duration,
-            total_deposits: 0,
-            total_yield: 0,
-            winner: None,
-            is_active: true,
-            player_count: 0,
-        };
+            .get(&DataKey::UnclaimedPolicy(asset.clone()))
+            .unwrap_or(UnclaimedPolicy::Hold);
+        match policy {
+            UnclaimedPolicy::Hold => {
+                add_i128(&env, &DataKey::UnclaimedFunds(asset.clone()), amount)
+            }
+            UnclaimedPolicy::Jackpot => add_to_jackpot(&env, &asset, amount),
+        }
+
+        amount
+    }
+
+    pub fn set_unclaimed_policy(env: Env, asset: Address, policy: UnclaimedPolicy) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
 
         env.storage()
             .instance()
-            .set(&DataKey::CurrentRound, &new_round_id);
+            .set(&DataKey::UnclaimedPolicy(asset.clone()), &policy);
+
         env.storage()
-            .persistent()
-            .set(&DataKey::Round(new_round_id), &new_round);
-        env.storage().persistent().set(
-            &DataKey::PlayerList(new_round_id),
-            &Vec::<Address>::new(&env),
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    /// Pay swept funds out of the unclaimed bucket, e.g. back to a player who missed the window
+    pub fn return_unclaimed(env: Env, asset: Address, to: Address, amount: i128) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
+
+        if amount <= 0 || amount > get_i128(&env, &DataKey::UnclaimedFunds(asset.clone())) {
+            panic!("Invalid unclaimed amount");
+        }
+        add_i128(&env, &DataKey::UnclaimedFunds(asset.clone()), -amount);
+
+        env.invoke_contract::<()>(
+            &asset,
+            &Symbol::new(&env, "transfer"),
+            (env.current_contract_address(), to.clone(), amount).into_val(&env),
+        );
+
+        env.events().publish(
+            (symbol_short!("unclaimed"), asset.clone(), to),
+            (amount, asset_decimals(&env, &asset)),
         );
+    }
+
+    /// Move swept funds from the unclaimed bucket into the current round's jackpot
+    pub fn roll_unclaimed(env: Env, asset: Address, amount: i128) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        require_asset(&env, &asset);
 
-        winner
+        if amount <= 0 || amount > get_i128(&env, &DataKey::UnclaimedFunds(asset.clone())) {
+            panic!("Invalid unclaimed amount");
+        }
+        add_i128(&env, &DataKey::UnclaimedFunds(asset.clone()), -amount);
+        add_to_jackpot(&env, &asset, amount);
     }
 
-    /// Claim refund for non-winners
-    pub fn claim_refund(env: Env, player: Address, round_id: u32) {
-        player.require_auth();
+    pub fn get_unclaimed_funds(env: Env, asset: Address) -> i128 {
+        get_i128(&env, &DataKey::UnclaimedFunds(asset.clone()))
+    }
 
+    /// Keep a round's own entries alive. Anyone can call this.
+    pub fn bump_round(env: Env, asset: Address, round_id: u32) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        let round_key = DataKey::Round(asset.clone(), round_id);
+        let summary_key = DataKey::RoundSummary(asset.clone(), round_id);
+        let has_round = env.storage().persistent().has(&round_key);
+        let has_summary = env.storage().persistent().has(&summary_key);
+        if !has_round && !has_summary {
+            panic!("Round not found");
+        }
+        if has_round {
+            bump(&env, &round_key);
+        }
+        if has_summary {
+            bump(&env, &summary_key);
+        }
+
+        let draw_key = DataKey::Draw(asset.clone(), round_id);
+        if env.storage().persistent().has(&draw_key) {
+            bump(&env, &draw_key);
+        }
+    }
+
+    /// Keep the player entries of a round alive, `limit` players at a time
+    /// starting from `start`. Anyone can call this.
+    pub fn bump_players(env: Env, asset: Address, round_id: u32, start: u32, limit: u32) {
         let round: Round = env
             .storage()
             .persistent()
-            .get(&DataKey::Round(round_id))
+            .get(&DataKey::Round(asset.clone(), round_id))
             .unwrap_or_else(|| panic!("Round not found"));
 
-        if round.is_active {
-            panic!("Round is still active");
+        let end = start.saturating_add(limit).min(round.player_count);
+        for index in start..end {
+            let index_key = DataKey::PlayerAt(asset.clone(), round_id, index);
+            // Savings-mode rounds keep no per-round entries
+            if let Some(player) = env.storage().persistent().get::<_, Address>(&index_key) {
+                bump(&env, &index_key);
+                bump(
+                    &env,
+                    &DataKey::PlayerDeposit(asset.clone(), round_id, player),
+                );
+            }
         }
+    }
 
-        let winner = round
-            .winner
-            .as_ref()
-            .unwrap_or_else(|| panic!("No winner selected yet"));
-
-        if winner == &player {
-            panic!("Winner cannot claim refund");
-        }
+    /// Delete a finished round whose refunds and prize have all been claimed or
+    /// swept, keeping only its `RoundSummary`. Removes at most `limit` players
+    /// per call, two entries each, so about 20 fit in one transaction; returns
+    /// the number of players still to be removed.
+    pub fn prune_round(env: Env, asset: Address, round_id: u32, limit: u32) -> u32 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let player_key = DataKey::PlayerDeposit(round_id, player.clone());
-        let mut player_entry: PlayerEntry = env
+        let mut round: Round = env
             .storage()
             .persistent()
-            .get(&player_key)
-            .unwrap_or_else(|| panic!("Player not in this round"));
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
 
-        if player_entry.has_claimed {
-            panic!("Refund already claimed");
+        match round.status {
+            RoundStatus::Settled
+            | RoundStatus::RolledOver
+            | RoundStatus::Cancelled
+            | RoundStatus::EmergencyExited => {}
+            _ => panic!("Round is not finished"),
+        }
+        if round.outstanding > 0 {
+            panic!("Round still has unclaimed funds");
+        }
+        if limit == 0 {
+            panic!("Limit must be positive");
         }
 
-        // Transfer refund
-        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        let summary_key = DataKey::RoundSummary(asset.clone(), round_id);
+        if !env.storage().persistent().has(&summary_key) {
+            env.storage()
+                .persistent()
+                .set(&summary_key, &summarize(&round));
+            bump(&env, &summary_key);
+        }
 
-        env.invoke_contract::<()>(
-            &usdc_token,
-            &Symbol::new(&env, "transfer"),
-            (
-                env.current_contract_address(),
-                player.clone(),
-                player_entry.deposit,
-            )
-                .into_val(&env),
-        );
+        // Remove players from the end so an interrupted prune can resume
+        let stop = round.player_count.saturating_sub(limit);
+        while round.player_count > stop {
+            round.player_count -= 1;
+            let index_key = DataKey::PlayerAt(asset.clone(), round_id, round.player_count);
+            // Savings-mode rounds keep no per-round entries
+            if let Some(player) = env.storage().persistent().get::<_, Address>(&index_key) {
+                env.storage().persistent().remove(&DataKey::PlayerDeposit(
+                    asset.clone(),
+                    round_id,
+                    player,
+                ));
+                env.storage().persistent().remove(&index_key);
+            }
+        }
 
-        // Mark as claimed
-        player_entry.has_claimed = true;
-        env.storage().persistent().set(&player_key, &player_entry);
+        if round.player_count == 0 {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Round(asset.clone(), round_id));
+            env.events()
+                .publish((symbol_short!("pruned"), asset.clone(), round_id), ());
+        } else {
+            save_round(&env, &asset, &round);
+        }
 
-        // Emit event
-        env.events().publish(
-            (symbol_short!("refund"), player.clone()),
-            (round_id, player_entry.deposit),
-        );
+        round.player_count
+    }
+
+    /// Winner, prize, totals and seed of a round, whether or not it has been pruned
+    pub fn get_round_summary(env: Env, asset: Address, round_id: u32) -> RoundSummary {
+        let summary: Option<RoundSummary> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RoundSummary(asset.clone(), round_id));
+        if let Some(summary) = summary {
+            return summary;
+        }
+
+        let round: Round = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
+        let mut summary = summarize(&round);
+        summary.status = effective_status(&env, &round);
+        summary
+    }
+
+    pub fn get_asset(env: Env, asset: Address) -> AssetInfo {
+        require_asset(&env, &asset);
+        AssetInfo {
+            decimals: asset_decimals(&env, &asset),
+            min_deposit: env
+                .storage()
+                .instance()
+                .get(&DataKey::MinDeposit(asset.clone()))
+                .unwrap(),
+            token: asset,
+        }
+    }
+
+    /// Every asset with a sub-pool in this deployment
+    pub fn get_assets(env: Env) -> Vec<AssetInfo> {
+        let mut assets = Vec::new(&env);
+        for asset in pool_assets(&env).iter() {
+            assets.push_back(Self::get_asset(env.clone(), asset));
+        }
+        assets
     }
 
-    pub fn get_stats(env: Env) -> GlobalStats {
+    pub fn get_stats(env: Env, asset: Address) -> GlobalStats {
         let current_round_id: u32 = env
             .storage()
             .instance()
-            .get(&DataKey::CurrentRound)
+            .get(&DataKey::CurrentRound(asset.clone()))
             .unwrap_or(1);
 
         let total_volume: i128 = env
             .storage()
             .persistent()
-            .get(&DataKey::TotalVolume)
+            .get(&DataKey::TotalVolume(asset.clone()))
             .unwrap_or(0);
 
         let total_players: u32 = env
             .storage()
             .persistent()
-            .get(&DataKey::TotalPlayers)
+            .get(&DataKey::TotalPlayers(asset.clone()))
+            .unwrap_or(0);
+
+        let total_prizes_paid: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TotalPrizesPaid(asset.clone()))
             .unwrap_or(0);
 
         GlobalStats {
             total_rounds: current_round_id - 1,
             total_volume,
             total_players,
-            total_prizes_paid: total_volume / 100,
+            total_prizes_paid,
+            total_emissions: get_i128(&env, &DataKey::TotalEmissions(asset.clone())),
         }
     }
 
-    pub fn get_round(env: Env, round_id: u32) -> Round {
-        env.storage()
+    pub fn get_round(env: Env, asset: Address, round_id: u32) -> Round {
+        let mut round: Round = env
+            .storage()
             .persistent()
-            .get(&DataKey::Round(round_id))
-            .unwrap()
+            .get(&DataKey::Round(asset.clone(), round_id))
+            .unwrap_or_else(|| panic!("Round not found"));
+        round.status = effective_status(&env, &round);
+        round
     }
 
-    pub fn get_player_entry(env: Env, round_id: u32, player: Address) -> Option<PlayerEntry> {
+    pub fn get_player_entry(
+        env: Env,
+        asset: Address,
+        round_id: u32,
+        player: Address,
+    ) -> Option<PlayerEntry> {
         env.storage()
             .persistent()
-            .get(&DataKey::PlayerDeposit(round_id, player))
+            .get(&DataKey::PlayerDeposit(asset.clone(), round_id, player))
     }
 
-    pub fn get_players(env: Env, round_id: u32) -> Vec<Address> {
-        env.storage()
+    /// Players of a round in entry order, `limit` at a time starting from `start`.
+    /// Savings-mode rounds have no per-round players and return none.
+    pub fn get_players(
+        env: Env,
+        asset: Address,
+        round_id: u32,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Address> {
+        let player_count = env
+            .storage()
             .persistent()
-            .get(&DataKey::PlayerList(round_id))
-            .unwrap_or(Vec::new(&env))
+            .get::<_, Round>(&DataKey::Round(asset.clone(), round_id))
+            .map(|round| round.player_count)
+            .unwrap_or(0);
+
+        let mut players = Vec::new(&env);
+        let end = start.saturating_add(limit).min(player_count);
+        for index in start..end {
+            match env
+                .storage()
+                .persistent()
+                .get(&DataKey::PlayerAt(asset.clone(), round_id, index))
+            {
+                Some(player) => players.push_back(player),
+                None => break,
+            }
+        }
+        players
     }
 }
+
+#[cfg(test)]
+mod test;
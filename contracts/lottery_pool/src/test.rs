@@ -0,0 +1,1161 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use mock_blend_pool::{MockBlendPool, MockBlendPoolClient};
+use mock_swap_router::{MockSwapRouter, MockSwapRouterClient};
+use soroban_sdk::testutils::{Address as _, IssuerFlags, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+const ROUND_DURATION: u64 = 3600;
+const MIN_DEPOSIT: i128 = 100_000; // 0.01 of a 7-decimal asset
+const SETTLE_LIMIT: u32 = 20; // Players walked per draw transaction
+
+struct Setup<'a> {
+    env: Env,
+    asset: Address,
+    pool: LotteryPoolClient<'a>,
+    token: TokenClient<'a>,
+    minter: StellarAssetClient<'a>,
+}
+
+/// Pool on a fresh asset using the mock yield strategy, so no Blend pool is needed
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let asset = env.register_stellar_asset_contract_v2(admin.clone());
+    // Lets tests freeze a holder's balance
+    asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let pool = LotteryPoolClient::new(&env, &env.register(LotteryPool, ()));
+    pool.initialize(
+        &admin,
+        &asset.address(),
+        &Address::generate(&env),
+        &500,
+        &ROUND_DURATION,
+        &MIN_DEPOSIT,
+    );
+    pool.set_yield_strategy(&asset.address(), &YieldStrategy::Mock);
+
+    Setup {
+        asset: asset.address(),
+        token: TokenClient::new(&env, &asset.address()),
+        minter: StellarAssetClient::new(&env, &asset.address()),
+        pool,
+        env,
+    }
+}
+
+fn player(s: &Setup, balance: i128) -> Address {
+    let player = Address::generate(&s.env);
+    s.minter.mint(&player, &balance);
+    player
+}
+
+/// Enable swaps from a fresh token through the mock router, paying out
+/// `numerator / denominator` of the pool asset per unit in
+fn swap_token<'a>(s: &Setup<'a>, numerator: i128, denominator: i128) -> StellarAssetClient<'a> {
+    let admin = Address::generate(&s.env);
+    let token_in = s.env.register_stellar_asset_contract_v2(admin);
+    let router = MockSwapRouterClient::new(&s.env, &s.env.register(MockSwapRouter, ()));
+    router.initialize(&Address::generate(&s.env));
+    router.set_rate(
+        &token_in.address(),
+        &s.token.address,
+        &numerator,
+        &denominator,
+    );
+    s.minter.mint(&router.address, &(1_000 * MIN_DEPOSIT));
+    s.pool.set_swap_router(&router.address);
+    StellarAssetClient::new(&s.env, &token_in.address())
+}
+
+/// House money the Mock strategy pays yield out of
+fn fund_house(s: &Setup, amount: i128) {
+    s.minter.mint(&s.pool.address, &amount);
+}
+
+/// Mock Blend pool listing the pool asset as reserve 0
+fn blend_pool<'a>(s: &Setup<'a>) -> MockBlendPoolClient<'a> {
+    let blend = MockBlendPoolClient::new(&s.env, &s.env.register(MockBlendPool, ()));
+    blend.initialize(&Address::generate(&s.env));
+    blend.add_reserve(&s.token.address, &0, &true);
+    blend
+}
+
+/// Switch the pool to the Blend strategy over fresh mock pools with `weights`
+fn use_blend<'a>(s: &Setup<'a>, weights: &[u32]) -> std::vec::Vec<MockBlendPoolClient<'a>> {
+    let pools: std::vec::Vec<_> = weights.iter().map(|_| blend_pool(s)).collect();
+    let mut allocations = Vec::new(&s.env);
+    for (blend, weight) in pools.iter().zip(weights) {
+        allocations.push_back(PoolAllocation {
+            pool: blend.address.clone(),
+            weight: *weight,
+        });
+    }
+    s.pool.set_blend_pools(&s.asset, &allocations);
+    s.pool.set_yield_strategy(&s.asset, &YieldStrategy::Blend);
+    pools
+}
+
+/// Raise a Blend reserve's b_rate by `bps` and fund the interest it accrues
+fn accrue(s: &Setup, blend: &MockBlendPoolClient, bps: i128) {
+    let data = blend.get_reserve(&s.token.address).data;
+    let b_rate = data.b_rate * (10000 + bps) / 10000;
+    let interest =
+        data.b_supply * b_rate / BLEND_SCALAR_12 - data.b_supply * data.b_rate / BLEND_SCALAR_12;
+    s.minter.mint(&blend.address, &interest);
+    blend.set_b_rate(&s.token.address, &b_rate);
+}
+
+fn advance(env: &Env, seconds: u64) {
+    env.ledger().with_mut(|ledger| ledger.timestamp += seconds);
+}
+
+#[test]
+fn savings_draw_after_cancelled_round() {
+    let s = setup();
+    s.pool.set_pool_mode(&s.asset, &PoolMode::Savings);
+
+    let saver = player(&s, MIN_DEPOSIT);
+    s.pool.deposit(&s.asset, &saver, &MIN_DEPOSIT);
+
+    // A long cancelled round must not leave its weight in the pool-wide total
+    advance(&s.env, 100_000);
+    s.pool
+        .cancel_round(&s.asset, &1, &String::from_str(&s.env, "incident"));
+
+    advance(&s.env, ROUND_DURATION);
+    // Puts the winning ticket well past the new round's weight if the
+    // cancelled round were still counted
+    s.env
+        .ledger()
+        .with_mut(|ledger| ledger.sequence_number = 1_000_000_000);
+
+    assert_eq!(
+        s.pool.pick_winner(&s.asset, &SETTLE_LIMIT),
+        Some(saver.clone())
+    );
+    assert_eq!(s.pool.get_round(&s.asset, &2).status, RoundStatus::Settled);
+    assert_eq!(s.token.balance(&saver), 0);
+}
+
+#[test]
+fn sponsor_top_up_needs_beneficiary_consent() {
+    let s = setup();
+    let sponsor = player(&s, 10 * MIN_DEPOSIT);
+    let beneficiary = Address::generate(&s.env);
+
+    // A first single-round gift is the sponsor's call alone
+    s.pool
+        .enter_for(&s.asset, &sponsor, &beneficiary, &MIN_DEPOSIT, &1);
+    assert!(s
+        .env
+        .auths()
+        .iter()
+        .all(|(signer, _)| *signer != beneficiary));
+
+    // Touching the now existing entry needs the beneficiary as well
+    s.pool.enter_for(&s.asset, &sponsor, &beneficiary, &1, &1);
+    assert!(s
+        .env
+        .auths()
+        .iter()
+        .any(|(signer, _)| *signer == beneficiary));
+
+    let other = Address::generate(&s.env);
+    s.pool
+        .enter_for(&s.asset, &sponsor, &other, &MIN_DEPOSIT, &3);
+    assert!(s.env.auths().iter().any(|(signer, _)| *signer == other));
+}
+
+#[test]
+fn emergency_exit_refunds_current_round() {
+    let s = setup();
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, 2 * MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &bob, &(2 * MIN_DEPOSIT), &3);
+
+    s.pool
+        .emergency_exit(&s.asset, &String::from_str(&s.env, "blend incident"));
+    assert_eq!(
+        s.pool.get_round(&s.asset, &1).status,
+        RoundStatus::EmergencyExited
+    );
+    assert_eq!(s.pool.get_current_round(&s.asset).id, 2);
+
+    // Renewing deposits are refunded too rather than carried on
+    assert_eq!(s.pool.claim_refund(&s.asset, &alice, &1), None);
+    assert_eq!(s.pool.claim_refund(&s.asset, &bob, &1), None);
+    assert_eq!(s.token.balance(&alice), MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&bob), 2 * MIN_DEPOSIT);
+    assert_eq!(s.pool.get_current_round(&s.asset).total_deposits, 0);
+}
+
+#[test]
+#[should_panic(expected = "Minimum deposit is below 0.01 of the asset")]
+fn min_deposit_scales_with_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let asset = env.register_stellar_asset_contract_v2(admin.clone());
+    let pool = LotteryPoolClient::new(&env, &env.register(LotteryPool, ()));
+    // 0.001 of a 7-decimal asset
+    pool.initialize(
+        &admin,
+        &asset.address(),
+        &Address::generate(&env),
+        &500,
+        &ROUND_DURATION,
+        &10_000,
+    );
+}
+
+#[test]
+fn enter_with_swap_records_pool_asset_amount() {
+    let s = setup();
+    let token_in = swap_token(&s, 2, 1);
+    let player = Address::generate(&s.env);
+    token_in.mint(&player, &MIN_DEPOSIT);
+
+    s.pool.enter_with_swap(
+        &s.asset,
+        &player,
+        &token_in.address,
+        &MIN_DEPOSIT,
+        &(2 * MIN_DEPOSIT),
+        &1,
+    );
+
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.total_deposits, 2 * MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&s.pool.address), 2 * MIN_DEPOSIT);
+    assert_eq!(
+        TokenClient::new(&s.env, &token_in.address).balance(&player),
+        0
+    );
+}
+
+#[test]
+fn enter_with_swap_respects_min_amount_out() {
+    let s = setup();
+    let token_in = swap_token(&s, 2, 1);
+    let player = Address::generate(&s.env);
+    token_in.mint(&player, &MIN_DEPOSIT);
+
+    // The router pays out 2x, short of the 3x asked for
+    let result = s.pool.try_enter_with_swap(
+        &s.asset,
+        &player,
+        &token_in.address,
+        &MIN_DEPOSIT,
+        &(3 * MIN_DEPOSIT),
+        &1,
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        TokenClient::new(&s.env, &token_in.address).balance(&player),
+        MIN_DEPOSIT
+    );
+    assert_eq!(s.pool.get_current_round(&s.asset).total_deposits, 0);
+}
+
+#[test]
+#[should_panic(expected = "Deposit below minimum")]
+fn enter_with_swap_below_min_deposit() {
+    let s = setup();
+    let token_in = swap_token(&s, 1, 10);
+    let player = Address::generate(&s.env);
+    token_in.mint(&player, &MIN_DEPOSIT);
+
+    s.pool
+        .enter_with_swap(&s.asset, &player, &token_in.address, &MIN_DEPOSIT, &1, &1);
+}
+
+#[test]
+fn settle_pays_prize_and_refunds() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+
+    // Mock yield is 5% of the round's deposits
+    let round = s.pool.get_round(&s.asset, &1);
+    let total_yield = 3 * MIN_DEPOSIT * 500 / 10000;
+    assert_eq!(round.status, RoundStatus::Settled);
+    assert_eq!(round.total_yield, total_yield);
+    assert_eq!(round.prize, MIN_DEPOSIT + total_yield);
+    assert_eq!(s.pool.get_current_round(&s.asset).id, 2);
+
+    assert_eq!(s.pool.claim_prize(&s.asset, &1), None);
+    assert_eq!(s.token.balance(&winner), MIN_DEPOSIT + total_yield);
+    assert!(s.pool.try_claim_prize(&s.asset, &1).is_err());
+    assert!(s.pool.try_claim_refund(&s.asset, &winner, &1).is_err());
+
+    for p in players.iter().filter(|p| **p != winner) {
+        assert_eq!(s.pool.claim_refund(&s.asset, p, &1), None);
+        assert_eq!(s.token.balance(p), MIN_DEPOSIT);
+        assert!(s.pool.try_claim_refund(&s.asset, p, &1).is_err());
+    }
+    assert_eq!(s.pool.get_round(&s.asset, &1).outstanding, 0);
+}
+
+#[test]
+fn too_few_players_roll_over() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &1);
+
+    advance(&s.env, ROUND_DURATION);
+    assert_eq!(s.pool.pick_winner(&s.asset, &SETTLE_LIMIT), None);
+
+    // The yield waits in the next round's jackpot; deposits come back
+    assert_eq!(
+        s.pool.get_round(&s.asset, &1).status,
+        RoundStatus::RolledOver
+    );
+    assert_eq!(
+        s.pool.get_current_round(&s.asset).jackpot,
+        2 * MIN_DEPOSIT * 500 / 10000
+    );
+    assert!(s.pool.try_claim_prize(&s.asset, &1).is_err());
+    assert_eq!(s.pool.claim_refund(&s.asset, &alice, &1), None);
+    assert_eq!(s.pool.claim_refund(&s.asset, &bob, &1), None);
+    assert_eq!(s.token.balance(&alice), MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&bob), MIN_DEPOSIT);
+}
+
+#[test]
+fn renewing_entry_and_jackpot_carry_over() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &2);
+    s.pool.enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &1);
+
+    advance(&s.env, ROUND_DURATION);
+    assert_eq!(s.pool.pick_winner(&s.asset, &SETTLE_LIMIT), None);
+    let jackpot = 2 * MIN_DEPOSIT * 500 / 10000;
+
+    // Alice's deposit moved on to round 2 instead of becoming refundable
+    assert!(s.pool.try_claim_refund(&s.asset, &alice, &1).is_err());
+    assert_eq!(s.pool.claim_refund(&s.asset, &bob, &1), None);
+    let carried = s.pool.get_player_entry(&s.asset, &2, &alice).unwrap();
+    assert_eq!(carried.deposit, MIN_DEPOSIT);
+    assert_eq!(carried.rounds_remaining, 0);
+
+    let carol = player(&s, MIN_DEPOSIT);
+    let dave = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &carol, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &dave, &MIN_DEPOSIT, &1);
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.total_deposits, 3 * MIN_DEPOSIT);
+    assert_eq!(round.jackpot, jackpot);
+
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    let total_yield = 3 * MIN_DEPOSIT * 500 / 10000;
+    assert_eq!(
+        s.pool.get_round(&s.asset, &2).prize,
+        MIN_DEPOSIT + total_yield + jackpot
+    );
+
+    s.pool.claim_prize(&s.asset, &2);
+    assert_eq!(
+        s.token.balance(&winner),
+        MIN_DEPOSIT + total_yield + jackpot
+    );
+    for p in [&alice, &carol, &dave] {
+        if *p != winner {
+            s.pool.claim_refund(&s.asset, p, &2);
+            assert_eq!(s.token.balance(p), MIN_DEPOSIT);
+        }
+    }
+}
+
+#[test]
+fn savings_odds_follow_time_weighted_balance() {
+    let s = setup();
+    s.pool.set_pool_mode(&s.asset, &PoolMode::Savings);
+    fund_house(&s, 100 * MIN_DEPOSIT);
+
+    // Alice holds 1 unit all round; Bob holds 3 units for the last quarter
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, 3 * MIN_DEPOSIT);
+    s.pool.deposit(&s.asset, &alice, &MIN_DEPOSIT);
+    advance(&s.env, ROUND_DURATION * 3 / 4);
+    s.pool.deposit(&s.asset, &bob, &(3 * MIN_DEPOSIT));
+    advance(&s.env, ROUND_DURATION / 4);
+
+    // Aim the winning ticket at the first one past Alice's weight
+    let alice_weight = MIN_DEPOSIT * ROUND_DURATION as i128;
+    let timestamp = s.env.ledger().timestamp();
+    s.env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = (timestamp ^ alice_weight as u64) as u32;
+    });
+
+    assert_eq!(
+        s.pool.pick_winner(&s.asset, &SETTLE_LIMIT),
+        Some(bob.clone())
+    );
+    let round = s.pool.get_round(&s.asset, &1);
+    assert_eq!(round.status, RoundStatus::Settled);
+    assert_eq!(round.total_deposits, 4 * MIN_DEPOSIT);
+
+    // The prize is the yield alone; balances stay put
+    let total_yield = 4 * MIN_DEPOSIT * 500 / 10000;
+    assert_eq!(round.prize, total_yield);
+    s.pool.claim_prize(&s.asset, &1);
+    assert_eq!(s.token.balance(&bob), total_yield);
+    assert_eq!(
+        s.pool.get_account(&s.asset, &bob).unwrap().balance,
+        3 * MIN_DEPOSIT
+    );
+
+    assert_eq!(s.pool.withdraw(&s.asset, &alice, &MIN_DEPOSIT), None);
+    assert_eq!(s.token.balance(&alice), MIN_DEPOSIT);
+}
+
+#[test]
+fn draw_settles_across_batches() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    // Every third player renews into the next round
+    let players: std::vec::Vec<Address> = (0..45)
+        .map(|i| {
+            let p = player(&s, MIN_DEPOSIT);
+            s.pool
+                .enter_lottery(&s.asset, &p, &MIN_DEPOSIT, &if i % 3 == 0 { 2 } else { 1 });
+            p
+        })
+        .collect();
+
+    advance(&s.env, ROUND_DURATION);
+    assert_eq!(s.pool.pick_winner(&s.asset, &0), None);
+    assert_eq!(s.pool.get_round(&s.asset, &1).status, RoundStatus::Drawing);
+    assert!(s
+        .pool
+        .try_settle_batch(&s.asset, &1, &5, &SETTLE_LIMIT)
+        .is_err());
+
+    let mut cursor = 0;
+    let mut batches = 0;
+    while cursor < 45 {
+        cursor = s.pool.settle_batch(&s.asset, &1, &cursor, &SETTLE_LIMIT);
+        batches += 1;
+    }
+    assert_eq!(batches, 3);
+
+    let round = s.pool.get_round(&s.asset, &1);
+    assert_eq!(round.status, RoundStatus::Settled);
+    assert!(players.contains(&round.winner.unwrap()));
+    assert_eq!(
+        s.pool.get_current_round(&s.asset).total_deposits,
+        15 * MIN_DEPOSIT
+    );
+}
+
+#[test]
+fn savings_draw_settles_across_batches() {
+    let s = setup();
+    s.pool.set_pool_mode(&s.asset, &PoolMode::Savings);
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    let savers: std::vec::Vec<Address> = (0..45)
+        .map(|_| {
+            let saver = player(&s, MIN_DEPOSIT);
+            s.pool.deposit(&s.asset, &saver, &MIN_DEPOSIT);
+            saver
+        })
+        .collect();
+
+    // Aim at the eleventh account so the walk takes several batches
+    advance(&s.env, ROUND_DURATION);
+    let ticket = 10 * MIN_DEPOSIT * ROUND_DURATION as i128;
+    let timestamp = s.env.ledger().timestamp();
+    s.env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = (timestamp ^ ticket as u64) as u32;
+    });
+    assert_eq!(s.pool.pick_winner(&s.asset, &0), None);
+
+    let mut cursor = 0;
+    let mut batches = 0;
+    while cursor < 45 {
+        cursor = s.pool.settle_batch(&s.asset, &1, &cursor, &5);
+        batches += 1;
+    }
+    assert_eq!(batches, 3);
+    let round = s.pool.get_round(&s.asset, &1);
+    assert_eq!(round.status, RoundStatus::Settled);
+    assert_eq!(round.winner, Some(savers[10].clone()));
+
+    // Savings rounds list no per-round players
+    assert_eq!(s.pool.get_players(&s.asset, &1, &0, &10).len(), 0);
+    s.pool.bump_players(&s.asset, &1, &0, &10);
+}
+
+#[test]
+fn prune_round_in_batches() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    let players: std::vec::Vec<Address> = (0..45)
+        .map(|_| {
+            let p = player(&s, MIN_DEPOSIT);
+            s.pool.enter_lottery(&s.asset, &p, &MIN_DEPOSIT, &1);
+            p
+        })
+        .collect();
+
+    advance(&s.env, ROUND_DURATION);
+    s.pool.pick_winner(&s.asset, &0);
+    let mut cursor = 0;
+    while cursor < 45 {
+        cursor = s.pool.settle_batch(&s.asset, &1, &cursor, &SETTLE_LIMIT);
+    }
+    let winner = s.pool.get_round(&s.asset, &1).winner.unwrap();
+
+    // Nothing can go while refunds are still owed
+    assert!(s.pool.try_prune_round(&s.asset, &1, &20).is_err());
+    s.pool.claim_prize(&s.asset, &1);
+    for p in players.iter().filter(|p| **p != winner) {
+        s.pool.claim_refund(&s.asset, p, &1);
+    }
+
+    assert_eq!(s.pool.prune_round(&s.asset, &1, &20), 25);
+    assert_eq!(s.pool.prune_round(&s.asset, &1, &20), 5);
+    assert_eq!(s.pool.prune_round(&s.asset, &1, &20), 0);
+    assert!(s.pool.try_get_round(&s.asset, &1).is_err());
+    assert_eq!(s.pool.get_player_entry(&s.asset, &1, &players[0]), None);
+
+    let summary = s.pool.get_round_summary(&s.asset, &1);
+    assert_eq!(summary.winner, Some(winner));
+    assert_eq!(summary.player_count, 45);
+    assert_eq!(summary.total_deposits, 45 * MIN_DEPOSIT);
+}
+
+#[test]
+fn enter_batch_at_max_size() {
+    let s = setup();
+    let payer = player(&s, 100 * MIN_DEPOSIT);
+    let mut entries = Vec::new(&s.env);
+    for _ in 0..MAX_BATCH_ENTRIES {
+        entries.push_back((Address::generate(&s.env), MIN_DEPOSIT));
+    }
+    s.pool.enter_batch(&s.asset, &payer, &entries);
+
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.player_count, MAX_BATCH_ENTRIES);
+    assert_eq!(
+        round.total_deposits,
+        MAX_BATCH_ENTRIES as i128 * MIN_DEPOSIT
+    );
+    assert_eq!(
+        s.token.balance(&payer),
+        (100 - MAX_BATCH_ENTRIES as i128) * MIN_DEPOSIT
+    );
+}
+
+#[test]
+#[should_panic(expected = "Too many entries in one batch")]
+fn enter_batch_over_max_size() {
+    let s = setup();
+    let payer = player(&s, 100 * MIN_DEPOSIT);
+    let mut entries = Vec::new(&s.env);
+    for _ in 0..=MAX_BATCH_ENTRIES {
+        entries.push_back((Address::generate(&s.env), MIN_DEPOSIT));
+    }
+    s.pool.enter_batch(&s.asset, &payer, &entries);
+}
+
+#[test]
+fn deposit_caps_limit_entries() {
+    let s = setup();
+    s.pool.set_deposit_caps(
+        &s.asset,
+        &(2 * MIN_DEPOSIT),
+        &(10 * MIN_DEPOSIT),
+        &2,
+        &false,
+    );
+    let alice = player(&s, 10 * MIN_DEPOSIT);
+    let bob = player(&s, 10 * MIN_DEPOSIT);
+    let carol = player(&s, 10 * MIN_DEPOSIT);
+
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    // Per-player cap
+    assert!(s.pool.try_enter_lottery(&s.asset, &alice, &1, &1).is_err());
+
+    s.pool.enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &1);
+    // Player cap
+    assert!(s
+        .pool
+        .try_enter_lottery(&s.asset, &carol, &MIN_DEPOSIT, &1)
+        .is_err());
+    let capacity = s.pool.get_current_capacity(&s.asset);
+    assert_eq!(capacity.remaining_players, Some(0));
+    assert_eq!(capacity.remaining_deposits, Some(7 * MIN_DEPOSIT));
+
+    // Round deposit cap
+    s.pool
+        .set_deposit_caps(&s.asset, &0, &(4 * MIN_DEPOSIT), &0, &false);
+    s.pool.enter_lottery(&s.asset, &carol, &MIN_DEPOSIT, &1);
+    assert!(s
+        .pool
+        .try_enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &1)
+        .is_err());
+    assert_eq!(
+        s.pool.get_current_capacity(&s.asset).remaining_deposits,
+        Some(0)
+    );
+}
+
+#[test]
+fn full_round_closes_early() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    s.pool.set_deposit_caps(&s.asset, &0, &0, &3, &true);
+    for _ in 0..3 {
+        let p = player(&s, MIN_DEPOSIT);
+        s.pool.enter_lottery(&s.asset, &p, &MIN_DEPOSIT, &1);
+    }
+
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.status, RoundStatus::AwaitingDraw);
+    assert_eq!(round.end_time, s.env.ledger().timestamp());
+    let late = player(&s, MIN_DEPOSIT);
+    assert!(s
+        .pool
+        .try_enter_lottery(&s.asset, &late, &MIN_DEPOSIT, &1)
+        .is_err());
+
+    // Drawable straight away
+    assert!(s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).is_some());
+}
+
+#[test]
+fn renewals_bypass_caps() {
+    let s = setup();
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &2);
+    s.pool.enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &2);
+
+    advance(&s.env, ROUND_DURATION);
+    s.pool.pick_winner(&s.asset, &0);
+
+    // Round 2 is one player short of its cap when the renewals arrive
+    s.pool.set_deposit_caps(&s.asset, &0, &0, &3, &true);
+    let carol = player(&s, MIN_DEPOSIT);
+    let dave = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &carol, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &dave, &MIN_DEPOSIT, &1);
+    assert_eq!(s.pool.get_current_round(&s.asset).status, RoundStatus::Open);
+
+    // Committed deposits are carried regardless, and close the full round
+    s.pool.settle_batch(&s.asset, &1, &0, &SETTLE_LIMIT);
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.status, RoundStatus::AwaitingDraw);
+    assert_eq!(round.player_count, 4);
+    assert_eq!(round.total_deposits, 4 * MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_current_capacity(&s.asset).remaining_players,
+        Some(0)
+    );
+}
+
+#[test]
+fn withdraw_entry_gives_up_ticket_and_pays_fee_to_jackpot() {
+    let s = setup();
+    s.pool.set_early_exit_fee(&s.asset, &100); // 1%
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, 3 * MIN_DEPOSIT);
+    let carol = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &bob, &(3 * MIN_DEPOSIT), &1);
+    s.pool.enter_lottery(&s.asset, &carol, &MIN_DEPOSIT, &1);
+
+    // A full withdrawal moves the last player into the freed slot
+    assert_eq!(s.pool.withdraw_entry(&s.asset, &alice, &MIN_DEPOSIT), None);
+    let fee = MIN_DEPOSIT / 100;
+    assert_eq!(s.token.balance(&alice), MIN_DEPOSIT - fee);
+    assert_eq!(s.pool.get_player_entry(&s.asset, &1, &alice), None);
+    assert_eq!(
+        s.pool.get_players(&s.asset, &1, &0, &10),
+        vec![&s.env, carol.clone(), bob.clone()]
+    );
+    assert_eq!(
+        s.pool.get_player_entry(&s.asset, &1, &carol).unwrap().index,
+        0
+    );
+
+    let round = s.pool.get_current_round(&s.asset);
+    assert_eq!(round.player_count, 2);
+    assert_eq!(round.total_deposits, 4 * MIN_DEPOSIT);
+    assert_eq!(round.jackpot, fee);
+
+    // A partial withdrawal must leave at least the minimum behind
+    assert!(s
+        .pool
+        .try_withdraw_entry(&s.asset, &bob, &(3 * MIN_DEPOSIT - 1))
+        .is_err());
+    s.pool.withdraw_entry(&s.asset, &bob, &MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_player_entry(&s.asset, &1, &bob).unwrap().deposit,
+        2 * MIN_DEPOSIT
+    );
+    assert_eq!(s.pool.get_current_round(&s.asset).jackpot, 2 * fee);
+}
+
+#[test]
+#[should_panic(expected = "Remaining deposit below minimum")]
+fn withdraw_entry_keeps_minimum() {
+    let s = setup();
+    let bob = player(&s, 3 * MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &bob, &(3 * MIN_DEPOSIT), &1);
+    s.pool
+        .withdraw_entry(&s.asset, &bob, &(3 * MIN_DEPOSIT - 1));
+}
+
+#[test]
+fn expired_claims_are_swept_and_returned() {
+    let s = setup();
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    s.pool.set_claim_window(&s.asset, &1000);
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    let losers: std::vec::Vec<&Address> = players.iter().filter(|p| **p != winner).collect();
+    s.pool.claim_refund(&s.asset, losers[0], &1);
+
+    assert!(s.pool.try_sweep_unclaimed(&s.asset, &1).is_err());
+    advance(&s.env, 1001);
+    assert!(s.pool.try_claim_refund(&s.asset, losers[1], &1).is_err());
+    assert!(s.pool.try_claim_prize(&s.asset, &1).is_err());
+
+    // The prize and the missed refund move to the unclaimed bucket
+    let prize = s.pool.get_round(&s.asset, &1).prize;
+    assert_eq!(s.pool.sweep_unclaimed(&s.asset, &1), prize + MIN_DEPOSIT);
+    assert_eq!(s.pool.get_unclaimed_funds(&s.asset), prize + MIN_DEPOSIT);
+    assert!(s.pool.try_sweep_unclaimed(&s.asset, &1).is_err());
+
+    s.pool.return_unclaimed(&s.asset, losers[1], &MIN_DEPOSIT);
+    assert_eq!(s.token.balance(losers[1]), MIN_DEPOSIT);
+    assert!(s
+        .pool
+        .try_return_unclaimed(&s.asset, losers[1], &(prize + 1))
+        .is_err());
+
+    let jackpot = s.pool.get_current_round(&s.asset).jackpot;
+    s.pool.roll_unclaimed(&s.asset, &prize);
+    assert_eq!(s.pool.get_current_round(&s.asset).jackpot, jackpot + prize);
+    assert_eq!(s.pool.get_unclaimed_funds(&s.asset), 0);
+}
+
+#[test]
+fn swept_claims_can_go_straight_to_jackpot() {
+    let s = setup();
+    s.pool.set_claim_window(&s.asset, &1000);
+    s.pool
+        .set_unclaimed_policy(&s.asset, &UnclaimedPolicy::Jackpot);
+    let alice = player(&s, MIN_DEPOSIT);
+    let bob = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &alice, &MIN_DEPOSIT, &1);
+    s.pool.enter_lottery(&s.asset, &bob, &MIN_DEPOSIT, &1);
+    advance(&s.env, ROUND_DURATION);
+    assert_eq!(s.pool.pick_winner(&s.asset, &SETTLE_LIMIT), None);
+
+    advance(&s.env, 1001);
+    let jackpot = s.pool.get_current_round(&s.asset).jackpot;
+    assert_eq!(s.pool.sweep_unclaimed(&s.asset, &1), 2 * MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_current_round(&s.asset).jackpot,
+        jackpot + 2 * MIN_DEPOSIT
+    );
+    assert_eq!(s.pool.get_unclaimed_funds(&s.asset), 0);
+}
+
+#[test]
+fn blend_interest_pays_the_prize() {
+    let s = setup();
+    let blend = &use_blend(&s, &[1])[0];
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+
+    // Deposits go straight to Blend and are tracked as the pool's principal
+    let exposure = s.pool.get_pool_exposure(&s.asset).get(0).unwrap();
+    assert_eq!(exposure.principal, 3 * MIN_DEPOSIT);
+    assert_eq!(exposure.value, 3 * MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&blend.address), 3 * MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&s.pool.address), 0);
+
+    accrue(&s, blend, 2500);
+    let interest = 3 * MIN_DEPOSIT / 4;
+    assert_eq!(
+        s.pool.get_pool_exposure(&s.asset).get(0).unwrap().value,
+        3 * MIN_DEPOSIT + interest
+    );
+
+    // The draw harvests the interest and leaves the principal supplied
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    assert_eq!(s.pool.get_round(&s.asset, &1).total_yield, interest);
+    let exposure = s.pool.get_pool_exposure(&s.asset).get(0).unwrap();
+    assert_eq!(exposure.principal, 3 * MIN_DEPOSIT);
+    assert_eq!(exposure.value, 3 * MIN_DEPOSIT);
+
+    assert_eq!(s.pool.claim_prize(&s.asset, &1), None);
+    assert_eq!(s.token.balance(&winner), MIN_DEPOSIT + interest);
+    for p in players.iter().filter(|p| **p != winner) {
+        assert_eq!(s.pool.claim_refund(&s.asset, p, &1), None);
+        assert_eq!(s.token.balance(p), MIN_DEPOSIT);
+    }
+    assert_eq!(
+        s.pool.get_pool_exposure(&s.asset).get(0).unwrap().principal,
+        0
+    );
+    assert_eq!(s.token.balance(&blend.address), 0);
+}
+
+#[test]
+fn harvest_emissions_swaps_blnd_into_the_jackpot() {
+    let s = setup();
+    let blend = &use_blend(&s, &[1])[0];
+    let blnd = swap_token(&s, 1, 4);
+    s.pool.set_emission_token(&blnd.address);
+
+    blnd.mint(&blend.address, &(40 * MIN_DEPOSIT));
+    blend.set_emissions(&blnd.address, &s.pool.address, &(40 * MIN_DEPOSIT));
+
+    assert!(s
+        .pool
+        .try_harvest_emissions(&s.asset, &(10 * MIN_DEPOSIT + 1))
+        .is_err());
+    assert_eq!(
+        s.pool.harvest_emissions(&s.asset, &(10 * MIN_DEPOSIT)),
+        10 * MIN_DEPOSIT
+    );
+    assert_eq!(s.pool.get_current_round(&s.asset).jackpot, 10 * MIN_DEPOSIT);
+    assert_eq!(s.pool.get_stats(&s.asset).total_emissions, 10 * MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&s.pool.address), 10 * MIN_DEPOSIT);
+
+    // Nothing left to claim
+    assert_eq!(s.pool.harvest_emissions(&s.asset, &0), 0);
+}
+
+#[test]
+fn rebalance_follows_weights_and_dropped_pools_pay_the_jackpot() {
+    let s = setup();
+    let first = &use_blend(&s, &[1])[0];
+    let second = blend_pool(&s);
+    let p = player(&s, 4 * MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &p, &(4 * MIN_DEPOSIT), &1);
+
+    let allocation = |blend: &MockBlendPoolClient, weight: u32| PoolAllocation {
+        pool: blend.address.clone(),
+        weight,
+    };
+    s.pool.set_blend_pools(
+        &s.asset,
+        &vec![&s.env, allocation(first, 1), allocation(&second, 1)],
+    );
+    assert_eq!(s.pool.rebalance(&s.asset), 2 * MIN_DEPOSIT);
+    for exposure in s.pool.get_pool_exposure(&s.asset).iter() {
+        assert_eq!(exposure.principal, 2 * MIN_DEPOSIT);
+    }
+    assert_eq!(s.token.balance(&first.address), 2 * MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&second.address), 2 * MIN_DEPOSIT);
+
+    // A pool holding principal can't be dropped
+    assert!(s
+        .pool
+        .try_set_blend_pools(&s.asset, &vec![&s.env, allocation(&second, 1)])
+        .is_err());
+
+    // Moving its principal out leaves the first pool with only its interest
+    accrue(&s, first, 1000);
+    s.pool.set_blend_pools(
+        &s.asset,
+        &vec![&s.env, allocation(first, 0), allocation(&second, 1)],
+    );
+    assert_eq!(s.pool.rebalance(&s.asset), 2 * MIN_DEPOSIT);
+    let exposure = s.pool.get_pool_exposure(&s.asset);
+    let leftover = exposure.get(0).unwrap();
+    assert_eq!(leftover.principal, 0);
+    assert!(leftover.value > 0);
+    assert_eq!(exposure.get(1).unwrap().principal, 4 * MIN_DEPOSIT);
+
+    // Dropping it harvests that interest into the jackpot
+    s.pool
+        .set_blend_pools(&s.asset, &vec![&s.env, allocation(&second, 1)]);
+    assert_eq!(s.pool.get_current_round(&s.asset).jackpot, leftover.value);
+    assert_eq!(s.token.balance(&s.pool.address), leftover.value);
+}
+
+#[test]
+fn refunds_queue_until_blend_has_liquidity() {
+    let s = setup();
+    let blend = &use_blend(&s, &[1])[0];
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    let losers: std::vec::Vec<_> = players.iter().filter(|p| **p != winner).collect();
+
+    // Most of the reserve is lent out, less than one refund is left
+    blend.set_borrowed(&s.token.address, &(5 * MIN_DEPOSIT / 2));
+    assert_eq!(s.pool.claim_refund(&s.asset, losers[0], &1), Some(0));
+    assert_eq!(s.pool.claim_refund(&s.asset, losers[1], &1), Some(1));
+    assert_eq!(s.token.balance(losers[0]), 0);
+    assert_eq!(s.pool.get_withdrawal_queue(&s.asset).total, 2 * MIN_DEPOSIT);
+    assert_eq!(s.pool.process_withdrawals(&s.asset, &10), 0);
+
+    // Repaid loans free the liquidity the queue waits for
+    blend.set_borrowed(&s.token.address, &0);
+    assert_eq!(s.pool.process_withdrawals(&s.asset, &10), 2);
+    for loser in losers.iter() {
+        assert_eq!(s.token.balance(loser), MIN_DEPOSIT);
+    }
+    let queue = s.pool.get_withdrawal_queue(&s.asset);
+    assert_eq!((queue.head, queue.tail, queue.total), (2, 2, 0));
+
+    assert_eq!(s.pool.claim_prize(&s.asset, &1), None);
+    assert_eq!(s.token.balance(&winner), MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_pool_exposure(&s.asset).get(0).unwrap().principal,
+        0
+    );
+}
+
+#[test]
+fn failed_queued_transfer_is_parked() {
+    let s = setup();
+    let blend = &use_blend(&s, &[1])[0];
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    let losers: std::vec::Vec<_> = players.iter().filter(|p| **p != winner).collect();
+
+    blend.set_borrowed(&s.token.address, &(3 * MIN_DEPOSIT));
+    assert_eq!(s.pool.claim_refund(&s.asset, losers[0], &1), Some(0));
+    assert_eq!(s.pool.claim_refund(&s.asset, losers[1], &1), Some(1));
+
+    // The first recipient can't receive the asset any more
+    s.minter.set_authorized(losers[0], &false);
+    blend.set_borrowed(&s.token.address, &0);
+    assert_eq!(s.pool.process_withdrawals(&s.asset, &10), 2);
+    assert_eq!(s.token.balance(losers[1]), MIN_DEPOSIT);
+    assert_eq!(s.token.balance(losers[0]), 0);
+    assert_eq!(s.pool.get_parked(&s.asset, losers[0]), MIN_DEPOSIT);
+    assert_eq!(s.pool.get_withdrawal_queue(&s.asset).total, 0);
+
+    // It collects the parked claim to another address
+    let other = Address::generate(&s.env);
+    assert_eq!(
+        s.pool.claim_parked(&s.asset, losers[0], &other),
+        MIN_DEPOSIT
+    );
+    assert_eq!(s.token.balance(&other), MIN_DEPOSIT);
+    assert_eq!(s.pool.get_parked(&s.asset, losers[0]), 0);
+    assert!(s
+        .pool
+        .try_claim_parked(&s.asset, losers[0], &other)
+        .is_err());
+}
+
+#[test]
+fn emergency_exit_queues_what_blend_cannot_release() {
+    let s = setup();
+    let blend = &use_blend(&s, &[1])[0];
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+    }
+
+    // Only one deposit's worth can leave Blend right now
+    blend.set_borrowed(&s.token.address, &(2 * MIN_DEPOSIT));
+    s.pool
+        .emergency_exit(&s.asset, &String::from_str(&s.env, "blend incident"));
+    assert_eq!(
+        s.pool.get_round(&s.asset, &1).status,
+        RoundStatus::EmergencyExited
+    );
+    assert_eq!(s.token.balance(&s.pool.address), MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_pool_exposure(&s.asset).get(0).unwrap().principal,
+        2 * MIN_DEPOSIT
+    );
+
+    assert_eq!(s.pool.claim_refund(&s.asset, &players[0], &1), None);
+    assert_eq!(s.token.balance(&players[0]), MIN_DEPOSIT);
+    assert_eq!(s.pool.claim_refund(&s.asset, &players[1], &1), Some(0));
+
+    // New deposits stay in the contract while the exit drains Blend
+    let newcomer = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &newcomer, &MIN_DEPOSIT, &1);
+    assert_eq!(s.token.balance(&blend.address), 2 * MIN_DEPOSIT);
+    assert!(s.pool.try_rebalance(&s.asset).is_err());
+
+    // Freed liquidity pays the queue and completes the exit
+    blend.set_borrowed(&s.token.address, &0);
+    assert_eq!(s.pool.process_withdrawals(&s.asset, &10), 1);
+    assert_eq!(s.token.balance(&players[1]), MIN_DEPOSIT);
+    assert_eq!(
+        s.pool.get_pool_exposure(&s.asset).get(0).unwrap().principal,
+        0
+    );
+    assert_eq!(s.token.balance(&blend.address), 0);
+
+    assert_eq!(s.pool.claim_refund(&s.asset, &players[2], &1), None);
+    assert_eq!(s.token.balance(&players[2]), MIN_DEPOSIT);
+    assert_eq!(s.token.balance(&s.pool.address), MIN_DEPOSIT);
+
+    // The pool is off Blend now
+    assert!(s.pool.try_rebalance(&s.asset).is_err());
+    let late = player(&s, MIN_DEPOSIT);
+    s.pool.enter_lottery(&s.asset, &late, &MIN_DEPOSIT, &1);
+    assert_eq!(s.token.balance(&blend.address), 0);
+    assert_eq!(s.token.balance(&s.pool.address), 2 * MIN_DEPOSIT);
+}
+
+#[test]
+fn sub_pools_keep_their_assets_apart() {
+    let s = setup();
+    let admin = Address::generate(&s.env);
+    let other = s.env.register_stellar_asset_contract_v2(admin);
+    let other_token = TokenClient::new(&s.env, &other.address());
+    let other_minter = StellarAssetClient::new(&s.env, &other.address());
+    let other_min = 10 * MIN_DEPOSIT;
+
+    s.pool.add_asset(
+        &other.address(),
+        &Address::generate(&s.env),
+        &500,
+        &(2 * ROUND_DURATION),
+        &other_min,
+    );
+    s.pool
+        .set_yield_strategy(&other.address(), &YieldStrategy::Mock);
+    assert!(s
+        .pool
+        .try_add_asset(
+            &s.asset,
+            &Address::generate(&s.env),
+            &500,
+            &ROUND_DURATION,
+            &MIN_DEPOSIT
+        )
+        .is_err());
+    let assets = s.pool.get_assets();
+    assert_eq!(assets.len(), 2);
+    assert_eq!(assets.get(1).unwrap().min_deposit, other_min);
+
+    // The same players enter both sub-pools
+    fund_house(&s, 100 * MIN_DEPOSIT);
+    other_minter.mint(&s.pool.address, &(100 * other_min));
+    let players = [
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+        player(&s, MIN_DEPOSIT),
+    ];
+    for p in players.iter() {
+        other_minter.mint(p, &other_min);
+        s.pool.enter_lottery(&s.asset, p, &MIN_DEPOSIT, &1);
+        s.pool.enter_lottery(&other.address(), p, &other_min, &1);
+    }
+    assert_eq!(
+        s.pool.get_current_round(&s.asset).total_deposits,
+        3 * MIN_DEPOSIT
+    );
+    assert_eq!(
+        s.pool.get_current_round(&other.address()).total_deposits,
+        3 * other_min
+    );
+
+    // Each sub-pool runs on its own schedule
+    advance(&s.env, ROUND_DURATION);
+    let winner = s.pool.pick_winner(&s.asset, &SETTLE_LIMIT).unwrap();
+    assert!(s
+        .pool
+        .try_pick_winner(&other.address(), &SETTLE_LIMIT)
+        .is_err());
+    assert_eq!(s.pool.get_current_round(&other.address()).id, 1);
+
+    // Prizes and refunds are paid in the sub-pool's own asset
+    s.pool.claim_prize(&s.asset, &1);
+    let total_yield = 3 * MIN_DEPOSIT * 500 / 10000;
+    assert_eq!(s.token.balance(&winner), MIN_DEPOSIT + total_yield);
+    assert_eq!(other_token.balance(&winner), 0);
+    assert!(s
+        .pool
+        .try_claim_refund(&other.address(), &winner, &1)
+        .is_err());
+
+    advance(&s.env, ROUND_DURATION);
+    let other_winner = s.pool.pick_winner(&other.address(), &SETTLE_LIMIT).unwrap();
+    s.pool.claim_prize(&other.address(), &1);
+    assert_eq!(
+        other_token.balance(&other_winner),
+        other_min + 3 * other_min * 500 / 10000
+    );
+    assert_eq!(
+        s.pool.get_stats(&other.address()).total_volume,
+        3 * other_min
+    );
+    assert_eq!(s.pool.get_stats(&s.asset).total_volume, 3 * MIN_DEPOSIT);
+}
+
+#[test]
+#[should_panic(expected = "No pool for this asset")]
+fn unknown_asset_has_no_pool() {
+    let s = setup();
+    let p = player(&s, MIN_DEPOSIT);
+    s.pool
+        .enter_lottery(&Address::generate(&s.env), &p, &MIN_DEPOSIT, &1);
+}
@@ -0,0 +1,296 @@
+#![no_std]
+//! Stand-in for a Blend v2 pool, used to exercise the lottery's Blend strategy
+//! in tests. It supports collateral supply and withdrawal, the reserve and
+//! position views the lottery reads, and BLND emission claims. Interest,
+//! borrowing and emissions are set by the admin rather than accrued; the pool
+//! pays them out of its own balance.
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, IntoVal, Map, Symbol, Vec};
+
+const SCALAR_12: i128 = 1_000_000_000_000;
+
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    Reserve(Address),   // asset -> MockReserve
+    Positions(Address), // user -> collateral b-tokens by reserve index
+    Emissions(Address), // user -> BLND claimable
+    EmissionToken,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct MockReserve {
+    pub index: u32,
+    pub enabled: bool,
+    pub b_rate: i128,
+    pub b_supply: i128,
+    pub d_supply: i128, // Borrowed, at a d_rate of 1
+}
+
+// Mirrors of the Blend v2 types the lottery decodes
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub request_type: u32,
+    pub address: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReserveConfig {
+    pub index: u32,
+    pub decimals: u32,
+    pub c_factor: u32,
+    pub l_factor: u32,
+    pub util: u32,
+    pub max_util: u32,
+    pub r_base: u32,
+    pub r_one: u32,
+    pub r_two: u32,
+    pub r_three: u32,
+    pub reactivity: u32,
+    pub supply_cap: i128,
+    pub enabled: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReserveData {
+    pub d_rate: i128,
+    pub b_rate: i128,
+    pub ir_mod: i128,
+    pub b_supply: i128,
+    pub d_supply: i128,
+    pub backstop_credit: i128,
+    pub last_time: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Reserve {
+    pub asset: Address,
+    pub config: ReserveConfig,
+    pub data: ReserveData,
+    pub scalar: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Positions {
+    pub liabilities: Map<u32, i128>,
+    pub collateral: Map<u32, i128>,
+    pub supply: Map<u32, i128>,
+}
+
+fn load_reserve(env: &Env, asset: &Address) -> MockReserve {
+    env.storage()
+        .instance()
+        .get(&DataKey::Reserve(asset.clone()))
+        .unwrap_or_else(|| panic!("Reserve not found"))
+}
+
+fn load_collateral(env: &Env, user: &Address) -> Map<u32, i128> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Positions(user.clone()))
+        .unwrap_or_else(|| Map::new(env))
+}
+
+fn require_admin(env: &Env) {
+    let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+    admin.require_auth();
+}
+
+#[contract]
+pub struct MockBlendPool;
+
+#[contractimpl]
+impl MockBlendPool {
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+    }
+
+    /// List `asset` as reserve `index` at a b_rate of 1
+    pub fn add_reserve(env: Env, asset: Address, index: u32, enabled: bool) {
+        require_admin(&env);
+
+        let reserve = MockReserve {
+            index,
+            enabled,
+            b_rate: SCALAR_12,
+            b_supply: 0,
+            d_supply: 0,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserve(asset), &reserve);
+    }
+
+    /// Simulate accrued interest; the pool must hold the extra tokens
+    pub fn set_b_rate(env: Env, asset: Address, b_rate: i128) {
+        require_admin(&env);
+
+        let mut reserve = load_reserve(&env, &asset);
+        reserve.b_rate = b_rate;
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserve(asset), &reserve);
+    }
+
+    /// Simulate `amount` of the reserve being lent out, and so not withdrawable
+    pub fn set_borrowed(env: Env, asset: Address, amount: i128) {
+        require_admin(&env);
+
+        let mut reserve = load_reserve(&env, &asset);
+        reserve.d_supply = amount;
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserve(asset), &reserve);
+    }
+
+    /// Make `amount` of `token` claimable by `user`
+    pub fn set_emissions(env: Env, token: Address, user: Address, amount: i128) {
+        require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::EmissionToken, &token);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Emissions(user), &amount);
+    }
+
+    pub fn submit(
+        env: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions {
+        from.require_auth();
+        if spender != from {
+            spender.require_auth();
+        }
+
+        let mut collateral = load_collateral(&env, &from);
+        for request in requests.iter() {
+            let mut reserve = load_reserve(&env, &request.address);
+            let held = collateral.get(reserve.index).unwrap_or(0);
+            match request.request_type {
+                2 => {
+                    if !reserve.enabled {
+                        panic!("Reserve is disabled");
+                    }
+                    env.invoke_contract::<()>(
+                        &request.address,
+                        &Symbol::new(&env, "transfer"),
+                        (
+                            spender.clone(),
+                            env.current_contract_address(),
+                            request.amount,
+                        )
+                            .into_val(&env),
+                    );
+                    let b_tokens = request.amount * SCALAR_12 / reserve.b_rate;
+                    collateral.set(reserve.index, held + b_tokens);
+                    reserve.b_supply += b_tokens;
+                }
+                3 => {
+                    let supplied = reserve.b_supply * reserve.b_rate / SCALAR_12;
+                    if request.amount > supplied - reserve.d_supply {
+                        panic!("Not enough liquidity");
+                    }
+                    // Round the b-tokens burned up, as Blend does
+                    let b_tokens =
+                        (request.amount * SCALAR_12 + reserve.b_rate - 1) / reserve.b_rate;
+                    if b_tokens > held {
+                        panic!("Not enough collateral");
+                    }
+                    collateral.set(reserve.index, held - b_tokens);
+                    reserve.b_supply -= b_tokens;
+                    env.invoke_contract::<()>(
+                        &request.address,
+                        &Symbol::new(&env, "transfer"),
+                        (env.current_contract_address(), to.clone(), request.amount).into_val(&env),
+                    );
+                }
+                _ => panic!("Unsupported request type"),
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::Reserve(request.address), &reserve);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::Positions(from.clone()), &collateral);
+
+        Self::get_positions(env, from)
+    }
+
+    pub fn get_reserve(env: Env, asset: Address) -> Reserve {
+        let reserve = load_reserve(&env, &asset);
+        Reserve {
+            asset,
+            config: ReserveConfig {
+                index: reserve.index,
+                decimals: 7,
+                c_factor: 0,
+                l_factor: 0,
+                util: 0,
+                max_util: 0,
+                r_base: 0,
+                r_one: 0,
+                r_two: 0,
+                r_three: 0,
+                reactivity: 0,
+                supply_cap: i128::MAX,
+                enabled: reserve.enabled,
+            },
+            data: ReserveData {
+                d_rate: SCALAR_12,
+                b_rate: reserve.b_rate,
+                ir_mod: 0,
+                b_supply: reserve.b_supply,
+                d_supply: reserve.d_supply,
+                backstop_credit: 0,
+                last_time: env.ledger().timestamp(),
+            },
+            scalar: 10_000_000,
+        }
+    }
+
+    pub fn get_positions(env: Env, user: Address) -> Positions {
+        Positions {
+            liabilities: Map::new(&env),
+            collateral: load_collateral(&env, &user),
+            supply: Map::new(&env),
+        }
+    }
+
+    /// Pay out the emissions set for `from`, whatever reserve tokens are named
+    pub fn claim(env: Env, from: Address, _reserve_token_ids: Vec<u32>, to: Address) -> i128 {
+        from.require_auth();
+
+        let key = DataKey::Emissions(from);
+        let amount: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if amount > 0 {
+            let token: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::EmissionToken)
+                .unwrap();
+            env.invoke_contract::<()>(
+                &token,
+                &Symbol::new(&env, "transfer"),
+                (env.current_contract_address(), to, amount).into_val(&env),
+            );
+            env.storage().persistent().set(&key, &0i128);
+        }
+        amount
+    }
+}
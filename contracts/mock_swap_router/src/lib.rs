@@ -0,0 +1,94 @@
+#![no_std]
+//! Stand-in for a Soroswap router on networks without liquidity, used to
+//! exercise `enter_with_swap`. It acts as its own pair: swaps pay out of the
+//! router's own balance at a fixed admin-set rate.
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, IntoVal, Symbol, Vec};
+
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    Rate(Address, Address), // (token_in, token_out) -> (numerator, denominator)
+}
+
+#[contract]
+pub struct MockSwapRouter;
+
+#[contractimpl]
+impl MockSwapRouter {
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+    }
+
+    /// Output per input as `numerator / denominator`, in base units
+    pub fn set_rate(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        numerator: i128,
+        denominator: i128,
+    ) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if numerator <= 0 || denominator <= 0 {
+            panic!("Rate must be positive");
+        }
+
+        env.storage().instance().set(
+            &DataKey::Rate(token_in, token_out),
+            &(numerator, denominator),
+        );
+    }
+
+    /// The router is its own pair
+    pub fn router_pair_for(env: Env, _token_a: Address, _token_b: Address) -> Address {
+        env.current_contract_address()
+    }
+
+    pub fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128> {
+        to.require_auth();
+
+        if env.ledger().timestamp() > deadline {
+            panic!("Deadline expired");
+        }
+        if path.len() != 2 {
+            panic!("Only direct paths are supported");
+        }
+
+        let token_in = path.get(0).unwrap();
+        let token_out = path.get(1).unwrap();
+        let (numerator, denominator): (i128, i128) = env
+            .storage()
+            .instance()
+            .get(&DataKey::Rate(token_in.clone(), token_out.clone()))
+            .unwrap_or_else(|| panic!("No rate for pair"));
+
+        let amount_out = amount_in * numerator / denominator;
+        if amount_out < amount_out_min {
+            panic!("Insufficient output amount");
+        }
+
+        env.invoke_contract::<()>(
+            &token_in,
+            &Symbol::new(&env, "transfer"),
+            (to.clone(), env.current_contract_address(), amount_in).into_val(&env),
+        );
+        env.invoke_contract::<()>(
+            &token_out,
+            &Symbol::new(&env, "transfer"),
+            (env.current_contract_address(), to, amount_out).into_val(&env),
+        );
+
+        Vec::from_array(&env, [amount_in, amount_out])
+    }
+}
@@ -169,7 +169,7 @@ stellar contract invoke \
   --network $NETWORK \
   -- initialize \
   --admin $(stellar keys address $ADMIN_KEY) \
-  --usdc_token $BLEND_USDC_ID \
+  --asset_token $BLEND_USDC_ID \
   --blend_pool $BLEND_POOL_ID \
   --yield_rate $YIELD_RATE \
   --round_duration $ROUND_DURATION \
@@ -183,7 +183,7 @@ stellar contract invoke \
   --network $NETWORK \
   -- initialize \
   --admin $(stellar keys address $ADMIN_KEY) \
-  --usdc_token $BLEND_USDC_ID \
+  --asset_token $BLEND_USDC_ID \
   --blend_pool $BLEND_POOL_ID \
   --yield_rate $YIELD_RATE \
   --round_duration $ROUND_DURATION \
@@ -197,7 +197,7 @@ stellar contract invoke \
   --network $NETWORK \
   -- initialize \
   --admin $(stellar keys address $ADMIN_KEY) \
-  --usdc_token $BLEND_USDC_ID \
+  --asset_token $BLEND_USDC_ID \
   --blend_pool $BLEND_POOL_ID \
   --yield_rate $YIELD_RATE \
   --round_duration $ROUND_DURATION \
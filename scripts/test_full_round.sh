@@ -106,8 +106,10 @@ stellar contract invoke \
   --source player1 \
   --network $NETWORK \
   -- enter_lottery \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER1_ADDRESS \
-  --amount $PLAYER1_ENTRY
+  --amount $PLAYER1_ENTRY \
+  --rounds 1
 echo "✓ Player1 entered!"
 
 echo ""
@@ -127,8 +129,10 @@ stellar contract invoke \
   --source player2 \
   --network $NETWORK \
   -- enter_lottery \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER2_ADDRESS \
-  --amount $PLAYER2_ENTRY
+  --amount $PLAYER2_ENTRY \
+  --rounds 1
 echo "✓ Player2 entered!"
 
 echo ""
@@ -148,8 +152,10 @@ stellar contract invoke \
   --source player3 \
   --network $NETWORK \
   -- enter_lottery \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER3_ADDRESS \
-  --amount $PLAYER3_ENTRY
+  --amount $PLAYER3_ENTRY \
+  --rounds 1
 echo "✓ Player3 entered!"
 
 # ============================================
@@ -164,7 +170,8 @@ stellar contract invoke \
   --id $SMALL_POOL_ID \
   --source player1 \
   --network $NETWORK \
-  -- get_current_round
+  -- get_current_round \
+  --asset $BLEND_USDC_ID
 
 echo ""
 echo "Player List:"
@@ -173,7 +180,10 @@ stellar contract invoke \
   --source player1 \
   --network $NETWORK \
   -- get_players \
-  --round_id 1
+  --asset $BLEND_USDC_ID \
+  --round_id 1 \
+  --start 0 \
+  --limit 50
 
 echo ""
 echo "Lottery contract balance (should be 0, funds in Blend):"
@@ -214,7 +224,7 @@ if [[ $REPLY =~ ^[Yy]$ ]]; then
     echo "✓ Round ended!"
 else
     echo "Skipping countdown. Run this when ready:"
-    echo "  stellar contract invoke --id $SMALL_POOL_ID --source admin --network testnet -- pick_winner"
+    echo "  stellar contract invoke --id $SMALL_POOL_ID --source admin --network testnet -- pick_winner --limit 20"
     exit 0
 fi
 
@@ -240,7 +250,9 @@ WINNER_OUTPUT=$(stellar contract invoke \
   --id $SMALL_POOL_ID \
   --source admin \
   --network $NETWORK \
-  -- pick_winner)
+  -- pick_winner \
+  --asset $BLEND_USDC_ID \
+  --limit 20)
 
 echo ""
 echo "🎉 WINNER SELECTED:"
@@ -263,13 +275,27 @@ stellar contract invoke \
   --source player1 \
   --network $NETWORK \
   -- get_round \
+  --asset $BLEND_USDC_ID \
   --round_id 1
 
 # ============================================
-# PHASE 6: CLAIM REFUNDS
+# PHASE 6: CLAIM PRIZE AND REFUNDS
 # ============================================
 echo ""
-echo "━━━ PHASE 6: Refund Claims ━━━"
+echo "━━━ PHASE 6: Prize and Refund Claims ━━━"
+echo ""
+
+echo "Winner claiming prize..."
+for PLAYER_KEY in player1 player2 player3; do
+  stellar contract invoke \
+    --id $SMALL_POOL_ID \
+    --source $PLAYER_KEY \
+    --network $NETWORK \
+    -- claim_prize \
+    --asset $BLEND_USDC_ID \
+    --round_id 1 2>/dev/null && echo "  → $PLAYER_KEY claimed the prize" && break
+done
+
 echo ""
 
 echo "Player1 claiming refund..."
@@ -278,6 +304,7 @@ stellar contract invoke \
   --source player1 \
   --network $NETWORK \
   -- claim_refund \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER1_ADDRESS \
   --round_id 1 2>&1 || echo "  → Player1 was winner or already claimed"
 
@@ -288,6 +315,7 @@ stellar contract invoke \
   --source player2 \
   --network $NETWORK \
   -- claim_refund \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER2_ADDRESS \
   --round_id 1 2>&1 || echo "  → Player2 was winner or already claimed"
 
@@ -298,6 +326,7 @@ stellar contract invoke \
   --source player3 \
   --network $NETWORK \
   -- claim_refund \
+  --asset $BLEND_USDC_ID \
   --player $PLAYER3_ADDRESS \
   --round_id 1 2>&1 || echo "  → Player3 was winner or already claimed"
 
@@ -358,7 +387,8 @@ stellar contract invoke \
   --id $SMALL_POOL_ID \
   --source player1 \
   --network $NETWORK \
-  -- get_stats
+  -- get_stats \
+  --asset $BLEND_USDC_ID
 
 # ============================================
 # RESULTS SUMMARY